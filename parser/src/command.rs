@@ -1,16 +1,22 @@
 use crate::error::Error;
 use crate::ignore_block::IgnoreBlocks;
-use crate::token::Tokenizer;
+use crate::token::{Token, Tokenizer};
 use regex::Regex;
+use std::fmt;
 
 pub mod assign;
 pub mod close;
 pub mod glacier;
+pub mod help;
+pub mod lock;
+pub mod milestone;
 pub mod nominate;
 pub mod note;
 pub mod ping;
 pub mod prioritize;
 pub mod relabel;
+pub mod remind;
+pub mod reopen;
 pub mod second;
 pub mod shortcut;
 pub mod transfer;
@@ -26,8 +32,129 @@ pub enum Command<'a> {
     Glacier(Result<glacier::GlacierCommand, Error<'a>>),
     Shortcut(Result<shortcut::ShortcutCommand, Error<'a>>),
     Close(Result<close::CloseCommand, Error<'a>>),
+    Reopen(Result<reopen::ReopenCommand, Error<'a>>),
+    Lock(Result<lock::LockCommand, Error<'a>>),
+    Milestone(Result<milestone::MilestoneCommand, Error<'a>>),
     Note(Result<note::NoteCommand, Error<'a>>),
     Transfer(Result<transfer::TransferCommand, Error<'a>>),
+    Remind(Result<remind::RemindCommand, Error<'a>>),
+    Help(Result<help::HelpCommand, Error<'a>>),
+    /// `@bot <word>` where `<word>` is close to, but not, a known command.
+    /// Always an error, suggesting the closest command.
+    Unknown(Error<'a>),
+}
+
+/// The words commands start with, used to suggest a command for mistyped
+/// ones.
+const COMMAND_WORDS: &[&str] = &[
+    "label",
+    "labels",
+    "modify",
+    "claim",
+    "assign",
+    "release-assignment",
+    "note",
+    "ping",
+    "nominate",
+    "beta-nominate",
+    "beta-accept",
+    "beta-approve",
+    "prioritize",
+    "second",
+    "seconded",
+    "glacier",
+    "ready",
+    "review",
+    "reviewer",
+    "author",
+    "blocked",
+    "close",
+    "reopen",
+    "lock",
+    "unlock",
+    "milestone",
+    "transfer",
+    "remind",
+    "reminders",
+    "help",
+];
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct UnknownCommand {
+    pub word: String,
+}
+
+impl std::error::Error for UnknownCommand {}
+
+impl fmt::Display for UnknownCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown command `{}`", self.word)
+    }
+}
+
+/// Returns the known command closest to `word`, if it is close enough that
+/// `word` is likely a typo of it rather than prose mentioning the bot.
+fn closest_command(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    // Inflections like `closed` or `assigned` are more likely to be prose.
+    if COMMAND_WORDS
+        .iter()
+        .any(|command| word.starts_with(command))
+    {
+        return None;
+    }
+    let (distance, command) = COMMAND_WORDS
+        .iter()
+        .map(|command| (edit_distance(&word, command), *command))
+        .min_by_key(|(distance, _)| *distance)?;
+    // Allow one typo for every three characters, and at most two.
+    if distance == 0 || distance > 2 || distance * 3 > word.chars().count() {
+        return None;
+    }
+    Some(command)
+}
+
+/// Whether `rest`, the rest of a line after a command, is only punctuation
+/// ending it.
+fn is_end_of_line(rest: &str) -> bool {
+    rest.trim_end_matches(['.', '?', '!']).is_empty()
+}
+
+/// Whether `line` parses as a known command which takes all of it.
+fn parses_whole_line(line: &str) -> bool {
+    match Input::parse_known(&Tokenizer::new(line)).as_mut_slice() {
+        [(tok, command)] => command.is_ok() && is_end_of_line(tok.rest_of_line()),
+        _ => false,
+    }
+}
+
+/// The optimal string alignment distance between `a` and `b`: the number of
+/// insertions, deletions, substitutions and transpositions of adjacent
+/// characters needed to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // `d[i][j]` is the distance between the first `i` characters of `a` and
+    // the first `j` characters of `b`.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
 }
 
 #[derive(Debug)]
@@ -36,7 +163,7 @@ pub struct Input<'a> {
     parsed: usize,
     ignore: IgnoreBlocks,
     /// A pattern for finding the start of a command based on the name of the
-    /// configured bots.
+    /// configured bots, or the command prefix if there is one.
     bot_re: Regex,
 }
 
@@ -61,11 +188,18 @@ where
 }
 
 impl<'a> Input<'a> {
-    pub fn new(input: &'a str, bot: Vec<&'a str>) -> Input<'a> {
-        let bots: Vec<_> = bot.iter().map(|bot| format!(r"(?:@{bot}\b)")).collect();
+    /// Finds the commands in `input` addressed to one of the `bot` names.
+    ///
+    /// With a `prefix`, a line starting with it is a command too, so that with
+    /// `/` both `@bot label +bug` and `/label +bug` work.
+    pub fn new(input: &'a str, bot: Vec<&'a str>, prefix: Option<&str>) -> Input<'a> {
+        let mut starts: Vec<_> = bot.iter().map(|bot| format!(r"(?:@{bot}\b)")).collect();
+        if let Some(prefix) = prefix {
+            starts.push(format!(r"(?m:^[ \t]*{})", regex::escape(prefix)));
+        }
         let bot_re = Regex::new(&format!(
-            r#"(?i)(?P<review>\br\?)|{bots}"#,
-            bots = bots.join("|")
+            r#"(?i)(?P<review>\br\?)|{starts}"#,
+            starts = starts.join("|")
         ))
         .unwrap();
         Input {
@@ -80,6 +214,29 @@ impl<'a> Input<'a> {
         let tok = Tokenizer::new(&self.all[self.parsed..]);
         log::info!("identified potential command");
 
+        let mut success = Self::parse_known(&tok);
+
+        if success.len() > 1 {
+            panic!(
+                "succeeded parsing {:?} to multiple commands: {:?}",
+                &self.all[self.parsed..],
+                success
+            );
+        }
+
+        let Some((mut tok, c)) = success.pop() else {
+            return Self::parse_unknown(&tok);
+        };
+        // if we errored out while parsing the command do not move the input forwards
+        if c.is_ok() {
+            self.parsed += tok.position();
+        }
+        Some(c)
+    }
+
+    /// Parses the input as each of the known commands, returning those it is
+    /// one of with the input left after them.
+    fn parse_known(tok: &Tokenizer<'a>) -> Vec<(Tokenizer<'a>, Command<'a>)> {
         let mut success = vec![];
 
         let original_tokenizer = tok.clone();
@@ -134,26 +291,63 @@ impl<'a> Input<'a> {
             Command::Close,
             &original_tokenizer,
         ));
+        success.extend(parse_single_command(
+            reopen::ReopenCommand::parse,
+            Command::Reopen,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            lock::LockCommand::parse,
+            Command::Lock,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            milestone::MilestoneCommand::parse,
+            Command::Milestone,
+            &original_tokenizer,
+        ));
         success.extend(parse_single_command(
             transfer::TransferCommand::parse,
             Command::Transfer,
             &original_tokenizer,
         ));
+        success.extend(parse_single_command(
+            remind::RemindCommand::parse,
+            Command::Remind,
+            &original_tokenizer,
+        ));
+        success.extend(parse_single_command(
+            help::HelpCommand::parse,
+            Command::Help,
+            &original_tokenizer,
+        ));
+        success
+    }
 
-        if success.len() > 1 {
-            panic!(
-                "succeeded parsing {:?} to multiple commands: {:?}",
-                &self.all[self.parsed..],
-                success
-            );
-        }
-
-        let (mut tok, c) = success.pop()?;
-        // if we errored out while parsing the command do not move the input forwards
-        if c.is_ok() {
-            self.parsed += tok.position();
+    /// Recognizes a mistyped command, when nothing parsed as a known one.
+    ///
+    /// With more text after the word, it is only taken for a typo if the
+    /// whole line is the suggested command, as in `@bot lable +T-lang`: in
+    /// `@bot clone this`, it is more likely prose mentioning the bot.
+    fn parse_unknown(tok: &Tokenizer<'a>) -> Option<Command<'a>> {
+        let mut tok = tok.clone();
+        let Ok(Some(Token::Word(word))) = tok.next_token() else {
+            return None;
+        };
+        let suggestion = closest_command(word)?;
+        let end = tok.position();
+        let rest = tok.clone().rest_of_line();
+        if !is_end_of_line(rest) && !parses_whole_line(&format!("{suggestion} {rest}")) {
+            log::info!("not suggesting `{suggestion}` for `{word}` followed by `{rest}`");
+            return None;
         }
-        Some(c)
+        let err = tok
+            .error(UnknownCommand {
+                word: word.to_string(),
+            })
+            .with_span(end - word.len()..end)
+            .with_suggestion(suggestion);
+        Some(Command::Unknown(err))
     }
 
     /// Parses command for `r?`
@@ -213,8 +407,14 @@ impl<'a> Command<'a> {
             Command::Glacier(r) => r.is_ok(),
             Command::Shortcut(r) => r.is_ok(),
             Command::Close(r) => r.is_ok(),
+            Command::Reopen(r) => r.is_ok(),
+            Command::Lock(r) => r.is_ok(),
+            Command::Milestone(r) => r.is_ok(),
             Command::Note(r) => r.is_ok(),
             Command::Transfer(r) => r.is_ok(),
+            Command::Remind(r) => r.is_ok(),
+            Command::Help(r) => r.is_ok(),
+            Command::Unknown(_) => false,
         }
     }
 
@@ -226,14 +426,14 @@ impl<'a> Command<'a> {
 #[test]
 fn errors_outside_command_are_fine() {
     let input = "haha\" unterminated quotes @bot labels +bug. Terminating after the command";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(input.next().unwrap().is_ok());
 }
 
 #[test]
 fn code_1() {
     let input = "`@bot modify label: +bug.`";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(input.next().is_none());
 }
 
@@ -242,7 +442,7 @@ fn code_2() {
     let input = "```
     @bot modify labels: +bug.
     ```";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(input.next().is_none());
 }
 
@@ -255,7 +455,7 @@ fn resumes_after_code() {
 
 @bot claim
     ";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(matches!(input.next(), Some(Command::Assign(Ok(_)))));
     assert_eq!(input.next(), None);
 }
@@ -263,25 +463,25 @@ fn resumes_after_code() {
 #[test]
 fn edit_1() {
     let input_old = "@bot modify labels: +bug.";
-    let mut input_old = Input::new(input_old, vec!["bot"]);
+    let mut input_old = Input::new(input_old, vec!["bot"], None);
     let input_new = "Adding labels: @bot modify label +bug. some other text";
-    let mut input_new = Input::new(input_new, vec!["bot"]);
+    let mut input_new = Input::new(input_new, vec!["bot"], None);
     assert_eq!(input_old.next(), input_new.next());
 }
 
 #[test]
 fn edit_2() {
     let input_old = "@bot label bug.";
-    let mut input_old = Input::new(input_old, vec!["bot"]);
+    let mut input_old = Input::new(input_old, vec!["bot"], None);
     let input_new = "@bot modify labels to: +bug.";
-    let mut input_new = Input::new(input_new, vec!["bot"]);
+    let mut input_new = Input::new(input_new, vec!["bot"], None);
     assert_eq!(input_old.next(), input_new.next());
 }
 
 #[test]
 fn move_input_along() {
     let input = "@bot labels: +bug. Afterwards, delete the world.";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(input.next().unwrap().is_ok());
     assert_eq!(&input.all[input.parsed..], " Afterwards, delete the world.");
 }
@@ -289,7 +489,7 @@ fn move_input_along() {
 #[test]
 fn move_input_along_1() {
     let input = "@bot modify labels\": +bug. Afterwards, delete the world.";
-    let mut input = Input::new(input, vec!["bot"]);
+    let mut input = Input::new(input, vec!["bot"], None);
     assert!(input.next().unwrap().is_err());
     // don't move input along if parsing the command fails
     assert_eq!(&input.all[..input.parsed], "@bot");
@@ -298,7 +498,7 @@ fn move_input_along_1() {
 #[test]
 fn multiname() {
     let input = "@rustbot label to: +bug. Afterwards, delete the world. @triagebot prioritize";
-    let mut input = Input::new(input, vec!["triagebot", "rustbot"]);
+    let mut input = Input::new(input, vec!["triagebot", "rustbot"], None);
     assert!(dbg!(input.next().unwrap()).is_ok());
     assert_eq!(
         &input.all[input.parsed..],
@@ -319,11 +519,12 @@ fn review_commands() {
         ("r? rust-lang/compiler", "rust-lang/compiler"),
         ("r? @D--a--s-h", "D--a--s-h"),
     ] {
-        let mut input = Input::new(input, vec!["bot"]);
+        let mut input = Input::new(input, vec!["bot"], None);
         assert_eq!(
             input.next(),
             Some(Command::Assign(Ok(assign::AssignCommand::ReviewName {
-                name: name.to_string()
+                names: vec![name.to_string()],
+                excluded: vec![],
             })))
         );
         assert_eq!(input.next(), None);
@@ -334,7 +535,7 @@ fn review_commands() {
 fn review_errors() {
     use std::error::Error;
     for input in ["r?", "r? @", "r? @ user", "r?:user", "r?! @foo", "r?\nline"] {
-        let mut input = Input::new(input, vec!["bot"]);
+        let mut input = Input::new(input, vec!["bot"], None);
         let err = match input.next() {
             Some(Command::Assign(Err(err))) => err,
             c => panic!("unexpected {:?}", c),
@@ -357,7 +558,95 @@ fn review_ignored() {
         "<a>\n r? @bot\n</a>",
         "<!--\nr? foo\n-->",
     ] {
-        let mut input = Input::new(input, vec!["bot"]);
+        let mut input = Input::new(input, vec!["bot"], None);
         assert_eq!(input.next(), None);
     }
 }
+
+#[test]
+fn suggests_mistyped_commands() {
+    for (input, snippet, suggestion) in [
+        ("@bot clam", "clam", "claim"),
+        ("@bot lable.", "lable", "label"),
+        ("@bot lable +T-lang", "lable", "label"),
+        ("@bot asign @octocat.", "asign", "assign"),
+        ("Could you @bot prioritise?", "prioritise", "prioritize"),
+    ] {
+        let mut input = Input::new(input, vec!["bot"], None);
+        match input.next() {
+            Some(Command::Unknown(err)) => {
+                assert_eq!(err.snippet(), snippet);
+                assert_eq!(err.suggestion.as_deref(), Some(suggestion));
+            }
+            c => panic!("unexpected {:?}", c),
+        }
+        assert_eq!(input.next(), None);
+    }
+}
+
+#[test]
+fn ignores_mentions_in_prose() {
+    for input in [
+        "thanks @bot for the help",
+        "@bot is great",
+        "cc @bot, maybe",
+        "@bot assigned this to me",
+        "@bot closed it",
+        "@bot clone this",
+        "@bot asign me to this",
+    ] {
+        let mut input = Input::new(input, vec!["bot"], None);
+        assert_eq!(input.next(), None);
+    }
+}
+
+#[test]
+fn edit_distance_counts_transpositions() {
+    assert_eq!(edit_distance("lable", "label"), 1);
+    assert_eq!(edit_distance("clam", "claim"), 1);
+    assert_eq!(edit_distance("note", "note"), 0);
+    assert_eq!(edit_distance("", "ping"), 4);
+}
+
+#[test]
+fn prefix_commands() {
+    let input = "/label +bug
+Some text, and/or a path/to/a/file.
+  /assign @octocat
+@bot prioritize
+";
+    let mut input = Input::new(input, vec!["bot"], Some("/"));
+    assert!(matches!(input.next(), Some(Command::Relabel(Ok(_)))));
+    assert!(matches!(input.next(), Some(Command::Assign(Ok(_)))));
+    assert!(matches!(input.next(), Some(Command::Prioritize(Ok(_)))));
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn prefix_commands_in_code() {
+    let input = "```
+/label +bug
+```
+
+> /close
+
+`/claim`";
+    let mut input = Input::new(input, vec!["bot"], Some("/"));
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn prefix_suggestions() {
+    let mut input = Input::new("/clone this\n/path/to/file", vec!["bot"], Some("/"));
+    assert_eq!(input.next(), None);
+    for line in ["/lable", "/lable +bug"] {
+        let mut input = Input::new(line, vec!["bot"], Some("/"));
+        assert!(matches!(input.next(), Some(Command::Unknown(_))));
+    }
+}
+
+#[test]
+fn no_prefix_by_default() {
+    let mut input = Input::new("/label +bug", vec!["bot"], None);
+    assert_eq!(input.next(), None);
+}
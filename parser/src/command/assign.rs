@@ -6,7 +6,14 @@
 //!
 //! ```text
 //! Command: `@bot claim`, `@bot release-assignment`, or `@bot assign @user`.
+//!
+//! Review: `r? <name>`, or `r? <name>, <name>...` for several reviewers,
+//! followed by any number of `!@user` to exclude users from being picked.
 //! ```
+//!
+//! A `<name>` is a user, team or group, with an optional `@`. To not mistake
+//! prose like `r? @user, can you look?` for a list, the names after the first
+//! one must start with `@` or be an `org/team` name.
 
 use crate::error::Error;
 use crate::token::{Token, Tokenizer};
@@ -16,8 +23,15 @@ use std::fmt;
 pub enum AssignCommand {
     Own,
     Release,
-    User { username: String },
-    ReviewName { name: String },
+    User {
+        username: String,
+    },
+    /// `r?` with the names of the reviewers to pick, in order, and the users
+    /// not to pick.
+    ReviewName {
+        names: Vec<String>,
+        excluded: Vec<String>,
+    },
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -55,6 +69,7 @@ impl AssignCommand {
             toks.next_token()?;
             if let Some(Token::Word(user)) = toks.next_token()? {
                 if user.starts_with('@') && user.len() != 1 {
+                    *input = toks;
                     Ok(Some(AssignCommand::User {
                         username: user[1..].to_owned(),
                     }))
@@ -80,16 +95,43 @@ impl AssignCommand {
 
     /// Parses the input for `r?` command.
     pub fn parse_review<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
-        match input.next_token() {
+        let mut names = match input.next_token() {
             Ok(Some(Token::Word(name))) => {
                 let name = name.strip_prefix('@').unwrap_or(name).to_string();
                 if name.is_empty() {
                     return Err(input.error(ParseError::NoUser));
                 }
-                Ok(Some(AssignCommand::ReviewName { name }))
+                vec![name]
+            }
+            _ => return Err(input.error(ParseError::NoUser)),
+        };
+        let mut excluded = Vec::new();
+        // The rest of the comment is often prose, so a token that doesn't
+        // parse, like an unterminated quote, ends the list rather than making
+        // the command fail.
+        loop {
+            let mut toks = input.clone();
+            let comma = toks.eat_token(Token::Comma).unwrap_or(false);
+            let and = toks.eat_token(Token::Word("and")).unwrap_or(false);
+            match toks.next_token().ok().flatten() {
+                Some(Token::Exclamation) => match toks.next_token().ok().flatten() {
+                    Some(Token::Word(user)) if user.len() > 1 && user.starts_with('@') => {
+                        excluded.push(user[1..].to_string());
+                    }
+                    _ => break,
+                },
+                // Names are only allowed before exclusions.
+                _ if !excluded.is_empty() => break,
+                _ if !comma && !and => break,
+                Some(Token::Word(name)) if name.len() > 1 && name.starts_with('@') => {
+                    names.push(name[1..].to_string());
+                }
+                Some(Token::Word(name)) if name.contains('/') => names.push(name.to_string()),
+                _ => break,
             }
-            _ => Err(input.error(ParseError::NoUser)),
+            *input = toks;
         }
+        Ok(Some(AssignCommand::ReviewName { names, excluded }))
     }
 }
 
@@ -155,7 +197,55 @@ mod tests {
             assert_eq!(
                 parse_review(input),
                 Ok(Some(AssignCommand::ReviewName {
-                    name: name.to_string()
+                    names: vec![name.to_string()],
+                    excluded: vec![],
+                })),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn review_multiple_names() {
+        for (input, names) in [
+            ("@a, @b", &["a", "b"][..]),
+            ("@a and rust-lang/libs", &["a", "rust-lang/libs"]),
+            ("compiler, @b, and @c.", &["compiler", "b", "c"]),
+            ("@a, can you review?", &["a"]),
+            ("@a and others", &["a"]),
+            ("@a, @", &["a"]),
+            ("@a \"unterminated", &["a"]),
+            ("@a, \"unterminated", &["a"]),
+            ("@a, @b \"unterminated", &["a", "b"]),
+        ] {
+            assert_eq!(
+                parse_review(input),
+                Ok(Some(AssignCommand::ReviewName {
+                    names: names.iter().map(|name| name.to_string()).collect(),
+                    excluded: vec![],
+                })),
+                "failed on {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn review_exclusions() {
+        for (input, names, excluded) in [
+            ("compiler !@a", &["compiler"][..], &["a"][..]),
+            ("compiler !@a !@b.", &["compiler"], &["a", "b"]),
+            ("@a, @b, !@c", &["a", "b"], &["c"]),
+            ("compiler !@a, @b", &["compiler"], &["a"]),
+            ("compiler !a", &["compiler"], &[]),
+            ("@user! thanks", &["user"], &[]),
+            ("compiler !@a \"unterminated", &["compiler"], &["a"]),
+            ("compiler !\"unterminated", &["compiler"], &[]),
+        ] {
+            assert_eq!(
+                parse_review(input),
+                Ok(Some(AssignCommand::ReviewName {
+                    names: names.iter().map(|name| name.to_string()).collect(),
+                    excluded: excluded.iter().map(|name| name.to_string()).collect(),
                 })),
                 "failed on {input}"
             );
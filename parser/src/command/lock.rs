@@ -0,0 +1,117 @@
+//! Parses the `@bot lock [reason]` and `@bot unlock` commands.
+//!
+//! The reason is one of `off-topic`, `too-heated` (or `"too heated"`),
+//! `resolved` and `spam`.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum LockCommand {
+    Lock { reason: Option<LockReason> },
+    Unlock,
+}
+
+/// The reasons GitHub accepts for locking a conversation.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum LockReason {
+    OffTopic,
+    TooHeated,
+    Resolved,
+    Spam,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    UnknownReason(String),
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnknownReason(reason) => write!(
+                f,
+                "unknown lock reason `{reason}`, \
+                 expected `off-topic`, `too-heated`, `resolved` or `spam`"
+            ),
+        }
+    }
+}
+
+impl LockCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        match input.peek_token()? {
+            Some(Token::Word("unlock")) => {
+                input.next_token()?;
+                Ok(Some(LockCommand::Unlock))
+            }
+            Some(Token::Word("lock")) => {
+                input.next_token()?;
+                let reason = match input.peek_token()? {
+                    Some(Token::Word(reason)) | Some(Token::Quote(reason)) => {
+                        let Some(reason) = LockReason::from_name(reason) else {
+                            return Err(input.error(ParseError::UnknownReason(reason.to_owned())));
+                        };
+                        input.next_token()?;
+                        Some(reason)
+                    }
+                    _ => None,
+                };
+                Ok(Some(LockCommand::Lock { reason }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl LockReason {
+    fn from_name(name: &str) -> Option<Self> {
+        match &*name.to_lowercase() {
+            "off-topic" => Some(LockReason::OffTopic),
+            "too-heated" | "too heated" => Some(LockReason::TooHeated),
+            "resolved" => Some(LockReason::Resolved),
+            "spam" => Some(LockReason::Spam),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+fn parse<'a>(input: &'a str) -> Result<Option<LockCommand>, Error<'a>> {
+    let mut toks = Tokenizer::new(input);
+    LockCommand::parse(&mut toks)
+}
+
+#[test]
+fn parse_lock() {
+    assert_eq!(parse("lock"), Ok(Some(LockCommand::Lock { reason: None })));
+    assert_eq!(
+        parse("lock too-heated."),
+        Ok(Some(LockCommand::Lock {
+            reason: Some(LockReason::TooHeated)
+        }))
+    );
+    assert_eq!(
+        parse(r#"lock "too heated""#),
+        Ok(Some(LockCommand::Lock {
+            reason: Some(LockReason::TooHeated)
+        }))
+    );
+    assert_eq!(parse("unlock"), Ok(Some(LockCommand::Unlock)));
+}
+
+#[test]
+fn parse_unknown_reason() {
+    use std::error::Error;
+    assert_eq!(
+        parse("lock heated")
+            .unwrap_err()
+            .source()
+            .unwrap()
+            .downcast_ref(),
+        Some(&ParseError::UnknownReason("heated".into())),
+    );
+}
@@ -0,0 +1,141 @@
+//! Parses the `@bot milestone <title>` and `@bot milestone remove` commands.
+//!
+//! The title is the rest of the line, up to a trailing `.` or another
+//! command, or a quoted string, so that a milestone named `remove` can be set
+//! with `@bot milestone "remove"`.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum MilestoneCommand {
+    Set { title: String },
+    Remove,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ParseError {
+    MissingTitle,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingTitle => write!(f, "missing milestone title"),
+        }
+    }
+}
+
+impl MilestoneCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        if !matches!(input.peek_token()?, Some(Token::Word("milestone"))) {
+            return Ok(None);
+        }
+        input.next_token()?;
+        let title = match input.peek_token()? {
+            Some(Token::Quote(title)) => {
+                input.next_token()?;
+                title
+            }
+            _ => {
+                let title = input.rest_of_line_before_mention();
+                match title.strip_suffix('.').unwrap_or(title).trim_end() {
+                    "remove" => return Ok(Some(MilestoneCommand::Remove)),
+                    title => title,
+                }
+            }
+        };
+        if title.is_empty() {
+            return Err(input.error(ParseError::MissingTitle));
+        }
+        Ok(Some(MilestoneCommand::Set {
+            title: title.to_owned(),
+        }))
+    }
+}
+
+#[cfg(test)]
+fn parse<'a>(input: &'a str) -> Result<Option<MilestoneCommand>, Error<'a>> {
+    let mut toks = Tokenizer::new(input);
+    MilestoneCommand::parse(&mut toks)
+}
+
+#[test]
+fn parse_set() {
+    assert_eq!(
+        parse("milestone 1.85.0"),
+        Ok(Some(MilestoneCommand::Set {
+            title: "1.85.0".into()
+        }))
+    );
+    assert_eq!(
+        parse("milestone Rust 2024\nThanks!"),
+        Ok(Some(MilestoneCommand::Set {
+            title: "Rust 2024".into()
+        }))
+    );
+    assert_eq!(
+        parse(r#"milestone "Rust 2024""#),
+        Ok(Some(MilestoneCommand::Set {
+            title: "Rust 2024".into()
+        }))
+    );
+    assert_eq!(
+        parse(r#"milestone "remove""#),
+        Ok(Some(MilestoneCommand::Set {
+            title: "remove".into()
+        }))
+    );
+}
+
+#[test]
+fn parse_remove() {
+    assert_eq!(
+        parse("milestone remove"),
+        Ok(Some(MilestoneCommand::Remove))
+    );
+    assert_eq!(
+        parse("milestone remove."),
+        Ok(Some(MilestoneCommand::Remove))
+    );
+}
+
+#[test]
+fn parse_trailing_period() {
+    assert_eq!(
+        parse("milestone 1.85.0."),
+        Ok(Some(MilestoneCommand::Set {
+            title: "1.85.0".into()
+        }))
+    );
+}
+
+#[test]
+fn parse_before_next_command() {
+    use crate::command::{Command, Input};
+    let mut input = Input::new("@bot milestone 1.85.0 @bot label +bug", vec!["bot"], None);
+    assert_eq!(
+        input.next(),
+        Some(Command::Milestone(Ok(MilestoneCommand::Set {
+            title: "1.85.0".into()
+        })))
+    );
+    assert!(matches!(input.next(), Some(Command::Relabel(Ok(_)))));
+    assert_eq!(input.next(), None);
+}
+
+#[test]
+fn parse_missing_title() {
+    use std::error::Error;
+    assert_eq!(
+        parse("milestone\n")
+            .unwrap_err()
+            .source()
+            .unwrap()
+            .downcast_ref(),
+        Some(&ParseError::MissingTitle),
+    );
+}
@@ -26,6 +26,9 @@
 //!
 //! <label>: \S+
 //! ```
+//!
+//! A `<label>` containing `*` or `[...]` is a glob pattern, e.g. `-S-*`
+//! removes all the `S-` labels. It is expanded by the handler.
 
 use crate::error::Error;
 use crate::token::{Token, Tokenizer};
@@ -74,6 +77,13 @@ impl Label {
     }
 }
 
+impl Label {
+    /// Whether this is a glob pattern rather than the name of a label.
+    pub fn is_pattern(&self) -> bool {
+        self.0.contains(['*', '['])
+    }
+}
+
 impl std::ops::Deref for Label {
     type Target = String;
     fn deref(&self) -> &String {
@@ -273,3 +283,18 @@ fn parse_shorter_command_with_to_colon() {
         ]))
     );
 }
+
+#[test]
+fn parse_patterns() {
+    let deltas = parse("labels -S-* +T-compiler.").unwrap().unwrap();
+    assert_eq!(
+        deltas,
+        vec![
+            LabelDelta::Remove(Label("S-*".into())),
+            LabelDelta::Add(Label("T-compiler".into())),
+        ]
+    );
+    assert!(deltas[0].label().is_pattern());
+    assert!(!deltas[1].label().is_pattern());
+    assert!(Label("A-[bc]*".into()).is_pattern());
+}
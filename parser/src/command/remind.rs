@@ -0,0 +1,271 @@
+//! Parses the `@bot remind` and `@bot reminders` commands.
+//!
+//! The grammar is as follows:
+//!
+//! ```text
+//! Command: `@bot remind <who> <when> [<message>]` or `@bot reminders`.
+//!
+//! <who>:
+//!  - me
+//!  - @<user>
+//!
+//! <when>:
+//!  - in <amount> <unit>, where <unit> is minute(s), hour(s), day(s) or week(s)
+//!  - on <YYYY-MM-DD>
+//! ```
+//!
+//! The message is the rest of the line, up to a trailing `.` or a word
+//! starting with `@`, which may be another command.
+
+use crate::error::Error;
+use crate::token::{Token, Tokenizer};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum RemindCommand {
+    /// Sets a reminder.
+    Add {
+        who: Target,
+        when: When,
+        message: Option<String>,
+    },
+    /// Lists the pending reminders.
+    List,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Target {
+    /// The user who wrote the command.
+    Me,
+    /// Another user, without the leading `@`.
+    User(String),
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum When {
+    /// After the given time from now.
+    In(Duration),
+    /// On the given day.
+    On(Date),
+}
+
+/// A calendar date, which is only checked to be plausible: the month is
+/// within 1-12 and the day within 1-31.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum ParseError {
+    MissingTarget,
+    MissingTime,
+    InvalidAmount,
+    InvalidUnit,
+    InvalidDate,
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingTarget => write!(f, "expected `me` or `@user`"),
+            ParseError::MissingTime => write!(f, "expected `in <amount> <unit>` or `on <date>`"),
+            ParseError::InvalidAmount => write!(f, "expected a positive number"),
+            ParseError::InvalidUnit => write!(f, "expected `minutes`, `hours`, `days` or `weeks`"),
+            ParseError::InvalidDate => write!(f, "expected a date like `2026-11-01`"),
+        }
+    }
+}
+
+impl RemindCommand {
+    pub fn parse<'a>(input: &mut Tokenizer<'a>) -> Result<Option<Self>, Error<'a>> {
+        match input.peek_token()? {
+            Some(Token::Word("reminders")) => {
+                input.next_token()?;
+                return Ok(Some(RemindCommand::List));
+            }
+            Some(Token::Word("remind")) => {
+                input.next_token()?;
+            }
+            _ => return Ok(None),
+        }
+
+        let who = match input.next_token()? {
+            Some(Token::Word("me")) => Target::Me,
+            Some(Token::Word(user)) if user.len() > 1 && user.starts_with('@') => {
+                Target::User(user[1..].to_owned())
+            }
+            _ => return Err(input.error(ParseError::MissingTarget)),
+        };
+
+        let when = match input.next_token()? {
+            Some(Token::Word("in")) => {
+                let amount = match input.next_token()? {
+                    Some(Token::Word(amount)) => amount.parse::<u64>().ok().filter(|&n| n > 0),
+                    _ => None,
+                };
+                let Some(amount) = amount else {
+                    return Err(input.error(ParseError::InvalidAmount));
+                };
+                let unit = match input.next_token()? {
+                    Some(Token::Word(unit)) => unit_in_seconds(unit),
+                    _ => None,
+                };
+                let Some(unit) = unit else {
+                    return Err(input.error(ParseError::InvalidUnit));
+                };
+                let Some(seconds) = amount.checked_mul(unit) else {
+                    return Err(input.error(ParseError::InvalidAmount));
+                };
+                When::In(Duration::from_secs(seconds))
+            }
+            Some(Token::Word("on")) => {
+                let date = match input.next_token()? {
+                    Some(Token::Word(date)) => parse_date(date),
+                    _ => None,
+                };
+                let Some(date) = date else {
+                    return Err(input.error(ParseError::InvalidDate));
+                };
+                When::On(date)
+            }
+            _ => return Err(input.error(ParseError::MissingTime)),
+        };
+
+        // A `.` ending the command or the message is not part of it.
+        let message = input.rest_of_line_before_mention();
+        let message = message.strip_suffix('.').unwrap_or(message).trim_end();
+        Ok(Some(RemindCommand::Add {
+            who,
+            when,
+            message: (!message.is_empty()).then(|| message.to_owned()),
+        }))
+    }
+}
+
+fn unit_in_seconds(unit: &str) -> Option<u64> {
+    match unit {
+        "minute" | "minutes" => Some(60),
+        "hour" | "hours" => Some(60 * 60),
+        "day" | "days" => Some(24 * 60 * 60),
+        "week" | "weeks" => Some(7 * 24 * 60 * 60),
+        _ => None,
+    }
+}
+
+fn parse_date(date: &str) -> Option<Date> {
+    let mut parts = date.split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let date = Date {
+        year: year.parse().ok()?,
+        month: month.parse().ok()?,
+        day: day.parse().ok()?,
+    };
+    ((1..=12).contains(&date.month) && (1..=31).contains(&date.day)).then_some(date)
+}
+
+#[cfg(test)]
+fn parse<'a>(input: &'a str) -> Result<Option<RemindCommand>, Error<'a>> {
+    let mut toks = Tokenizer::new(input);
+    RemindCommand::parse(&mut toks)
+}
+
+#[test]
+fn parse_relative() {
+    assert_eq!(
+        parse("remind me in 3 days to look at this again\nmore text"),
+        Ok(Some(RemindCommand::Add {
+            who: Target::Me,
+            when: When::In(Duration::from_secs(3 * 24 * 60 * 60)),
+            message: Some("to look at this again".into()),
+        }))
+    );
+    assert_eq!(
+        parse("remind me in 3 days."),
+        Ok(Some(RemindCommand::Add {
+            who: Target::Me,
+            when: When::In(Duration::from_secs(3 * 24 * 60 * 60)),
+            message: None,
+        }))
+    );
+    assert_eq!(
+        parse("remind me in 1 week to check the perf run."),
+        Ok(Some(RemindCommand::Add {
+            who: Target::Me,
+            when: When::In(Duration::from_secs(7 * 24 * 60 * 60)),
+            message: Some("to check the perf run".into()),
+        }))
+    );
+    assert_eq!(
+        parse("remind @ghost in 1 hour"),
+        Ok(Some(RemindCommand::Add {
+            who: Target::User("ghost".into()),
+            when: When::In(Duration::from_secs(60 * 60)),
+            message: None,
+        }))
+    );
+}
+
+#[test]
+fn parse_date_reminder() {
+    assert_eq!(
+        parse("remind @octocat on 2026-11-01"),
+        Ok(Some(RemindCommand::Add {
+            who: Target::User("octocat".into()),
+            when: When::On(Date {
+                year: 2026,
+                month: 11,
+                day: 1,
+            }),
+            message: None,
+        }))
+    );
+}
+
+#[test]
+fn parse_list() {
+    assert_eq!(parse("reminders"), Ok(Some(RemindCommand::List)));
+}
+
+#[test]
+fn parse_errors() {
+    fn error(input: &str) -> ParseError {
+        use std::error::Error;
+        let err = parse(input).unwrap_err();
+        *err.source().unwrap().downcast_ref::<ParseError>().unwrap()
+    }
+    assert_eq!(error("remind you in 3 days"), ParseError::MissingTarget);
+    assert_eq!(error("remind me tomorrow"), ParseError::MissingTime);
+    assert_eq!(error("remind me in 0 days"), ParseError::InvalidAmount);
+    assert_eq!(error("remind me in 3 fortnights"), ParseError::InvalidUnit);
+    assert_eq!(error("remind me on 2026-13-01"), ParseError::InvalidDate);
+    assert_eq!(error("remind me on 11/01/2026"), ParseError::InvalidDate);
+}
+
+#[test]
+fn parse_before_next_command() {
+    use crate::command::{Command, Input};
+    let mut input = Input::new(
+        "@bot remind me in 3 days @bot label +S-blocked",
+        vec!["bot"],
+        None,
+    );
+    assert_eq!(
+        input.next(),
+        Some(Command::Remind(Ok(RemindCommand::Add {
+            who: Target::Me,
+            when: When::In(Duration::from_secs(3 * 24 * 60 * 60)),
+            message: None,
+        })))
+    );
+    assert!(matches!(input.next(), Some(Command::Relabel(Ok(_)))));
+    assert_eq!(input.next(), None);
+}
@@ -1,16 +1,21 @@
 use std::error;
 use std::fmt;
+use std::ops::Range;
 
 #[derive(Debug)]
 pub struct Error<'a> {
     pub input: &'a str,
-    pub position: usize,
+    /// The bytes of `input` the error is about. Empty when the error is about
+    /// a position rather than a piece of the input.
+    pub span: Range<usize>,
     pub source: Box<dyn error::Error + Send>,
+    /// What the user probably meant to write instead of the spanned input.
+    pub suggestion: Option<String>,
 }
 
 impl<'a> PartialEq for Error<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.input == other.input && self.position == other.position
+        self.input == other.input && self.span == other.span
     }
 }
 
@@ -22,20 +27,54 @@ impl<'a> error::Error for Error<'a> {
 
 impl<'a> Error<'a> {
     pub fn position(&self) -> usize {
-        self.position
+        self.span.start
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// The offending part of the input.
+    pub fn snippet(&self) -> &'a str {
+        &self.input[self.span.clone()]
     }
 }
 
 impl<'a> fmt::Display for Error<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let space = 10;
-        let end = std::cmp::min(self.input.len(), self.position + space);
+        let Range { start, end } = self.span;
+        let after = std::cmp::min(self.input.len(), end + space);
         write!(
             f,
             "...'{}' | error: {} at >| '{}'...",
-            &self.input[self.position.saturating_sub(space)..self.position],
+            &self.input[floor_char_boundary(self.input, start.saturating_sub(space))..start],
             self.source,
-            &self.input[self.position..end],
-        )
+            &self.input[start..ceil_char_boundary(self.input, after)],
+        )?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "; did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index += 1;
     }
+    index
 }
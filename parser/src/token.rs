@@ -70,7 +70,7 @@ impl fmt::Display for ErrorKind {
 impl<'a> Error<'a> {
     fn position_and_kind(&self) -> (usize, ErrorKind) {
         (
-            self.position,
+            self.position(),
             *self.source.downcast_ref::<ErrorKind>().unwrap(),
         )
     }
@@ -86,10 +86,12 @@ impl<'a> Tokenizer<'a> {
     }
 
     pub fn error<T: 'static + std::error::Error + Send>(&mut self, source: T) -> Error<'a> {
+        let pos = self.cur_pos();
         Error {
             input: self.input,
-            position: self.cur_pos(),
+            span: pos..pos,
             source: Box::new(source),
+            suggestion: None,
         }
     }
 
@@ -210,6 +212,31 @@ impl<'a> Tokenizer<'a> {
         Ok(Some(Token::Word(&self.str_from(start))))
     }
 
+    /// Consumes the rest of the current line, without the line break, and
+    /// returns it with surrounding whitespace trimmed.
+    pub fn rest_of_line(&mut self) -> &'a str {
+        let start = self.cur_pos();
+        while self.cur().is_some_and(|(_, ch)| ch != '\n') {
+            self.advance();
+        }
+        self.str_from(start).trim()
+    }
+
+    /// Like [`Tokenizer::rest_of_line`], but stops before a word starting
+    /// with `@`, which may be the start of another command.
+    pub fn rest_of_line_before_mention(&mut self) -> &'a str {
+        let start = self.cur_pos();
+        let mut after_space = true;
+        while let Some((_, ch)) = self.cur() {
+            if ch == '\n' || (ch == '@' && after_space) {
+                break;
+            }
+            after_space = ch.is_whitespace();
+            self.advance();
+        }
+        self.str_from(start).trim()
+    }
+
     pub fn eat_token(&mut self, token: Token<'a>) -> Result<bool, Error<'a>> {
         match self.peek_token()? {
             Some(next_tok) if next_tok == token => {
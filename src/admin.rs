@@ -0,0 +1,203 @@
+//! Administrative endpoints for operating triagebot.
+//!
+//! These are only enabled when `TRIAGEBOT_ADMIN_TOKEN` is set, and requests
+//! must pass that token in an `Authorization: Bearer <token>` header.
+
+use crate::db::deliveries::{get_deliveries_between, get_delivery, WebhookDelivery};
+use crate::handlers::Context;
+use crate::{EventName, WebhookOutcome};
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use hyper::header::{HeaderMap, AUTHORIZATION};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Checks that the request carries the admin token.
+pub fn is_authorized(headers: &HeaderMap) -> bool {
+    let Ok(expected) = std::env::var("TRIAGEBOT_ADMIN_TOKEN") else {
+        return false;
+    };
+    let Some(token) = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    token.len() == expected.len() && openssl::memcmp::eq(token.as_bytes(), expected.as_bytes())
+}
+
+/// Handles `/admin/replay`, re-running the handlers for stored webhook
+/// deliveries.
+///
+/// The query string selects either a single delivery with
+/// `delivery=<X-GitHub-Delivery id>`, or all deliveries received in a time
+/// range with `since=<RFC 3339 timestamp>[&until=<RFC 3339 timestamp>]`.
+///
+/// Returns a summary with one line per replayed delivery.
+pub async fn replay(ctx: &Context, query: &str) -> anyhow::Result<String> {
+    let params: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    let deliveries = {
+        let db = ctx.db.get().await;
+        if let Some(id) = params.get("delivery") {
+            match get_delivery(&db, id).await? {
+                Some(delivery) => vec![delivery],
+                None => anyhow::bail!("no delivery with id {id} was recorded"),
+            }
+        } else if let Some(since) = params.get("since") {
+            let since = parse_timestamp(since).context("invalid `since`")?;
+            let until = match params.get("until") {
+                Some(until) => parse_timestamp(until).context("invalid `until`")?,
+                None => Utc::now(),
+            };
+            get_deliveries_between(&db, &since, &until).await?
+        } else {
+            anyhow::bail!("expected `?delivery=<id>` or `?since=<timestamp>[&until=<timestamp>]`");
+        }
+    };
+
+    let mut out = String::new();
+    for delivery in &deliveries {
+        let result = match replay_delivery(ctx, delivery).await {
+            Ok(WebhookOutcome::Processed) => "processed".to_string(),
+            Ok(WebhookOutcome::Ignored) => "ignored".to_string(),
+            // A delivery not yet handled when it was read may have been
+            // claimed by the queue or a redelivery since.
+            Ok(WebhookOutcome::AlreadyProcessed) => "already processed".to_string(),
+            Err(e) => format!("failed: {:?}", e),
+        };
+        writeln!(
+            out,
+            "{} {} ({}): {result}",
+            delivery.received_at.to_rfc3339(),
+            delivery.delivery_id,
+            delivery.event
+        )?;
+    }
+    writeln!(out, "replayed {} deliveries", deliveries.len())?;
+    Ok(out)
+}
+
+/// Handles `/admin/refresh-config?repo=<owner>/<name>`, dropping the cached
+/// `triagebot.toml` of a repository and loading it again.
+pub async fn refresh_config(ctx: &Context, query: &str) -> anyhow::Result<String> {
+    let params: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    let Some(repo) = params.get("repo") else {
+        anyhow::bail!("expected `?repo=<owner>/<name>`");
+    };
+    crate::config::refresh(&ctx.github, repo).await
+}
+
+/// Feeds a stored delivery through the handlers again.
+async fn replay_delivery(
+    ctx: &Context,
+    delivery: &WebhookDelivery,
+) -> Result<WebhookOutcome, crate::WebhookError> {
+    tracing::info!(
+        "replaying webhook delivery {} ({})",
+        delivery.delivery_id,
+        delivery.event
+    );
+    let Ok(event) = delivery.event.parse::<EventName>();
+    // A delivery that was never handled is claimed, so that GitHub sending it
+    // again doesn't handle it a second time.
+    let delivery_id = (!delivery.handled).then_some(delivery.delivery_id.as_str());
+    crate::webhook(event, delivery.payload.clone(), delivery_id, ctx).await
+}
+
+fn parse_timestamp(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(s)
+        .with_context(|| format!("`{s}` is not an RFC 3339 timestamp"))?
+        .with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay;
+    use crate::db::deliveries::{
+        claim_delivery, finish_delivery, get_deliveries_between, get_delivery, store_delivery,
+    };
+    use crate::tests::run_test;
+    use chrono::{Duration, Utc};
+
+    const PAYLOAD: &str = r#"{
+        "ref_type": "branch",
+        "repository": {"full_name": "rust-lang/triagebot", "default_branch": "master"},
+        "sender": {"login": "octocat", "id": 1}
+    }"#;
+
+    #[tokio::test]
+    async fn recorded_delivery_is_replayed() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            let received_at = Utc::now();
+            assert!(claim_delivery(&db, "1", "create", PAYLOAD, &received_at).await?);
+            finish_delivery(&db, "1", 42, &["failed".to_string()]).await?;
+
+            let delivery = get_delivery(&db, "1").await?.unwrap();
+            assert_eq!(delivery.event, "create");
+            assert_eq!(delivery.payload, PAYLOAD);
+            assert_eq!(delivery.duration_ms, 42);
+            assert_eq!(delivery.errors, ["failed"]);
+
+            let minute = Duration::minutes(1);
+            let around =
+                get_deliveries_between(&db, &(received_at - minute), &(received_at + minute))
+                    .await?;
+            assert_eq!(around.len(), 1);
+            let after =
+                get_deliveries_between(&db, &(received_at + minute), &(received_at + minute * 2))
+                    .await?;
+            assert!(after.is_empty());
+
+            let out = replay(ctx.handler_ctx(), "delivery=1").await?;
+            assert!(out.contains(" 1 (create): processed\n"), "{out}");
+            assert!(out.ends_with("replayed 1 deliveries\n"), "{out}");
+
+            let since = (received_at - minute).to_rfc3339();
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("since", &since)
+                .finish();
+            let out = replay(ctx.handler_ctx(), &query).await?;
+            assert!(out.ends_with("replayed 1 deliveries\n"), "{out}");
+
+            assert!(replay(ctx.handler_ctx(), "delivery=2").await.is_err());
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn queued_delivery_is_replayed() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            // A delivery that was queued, but cut off by a shutdown before it
+            // was handled.
+            store_delivery(&db, "1", "create", PAYLOAD, &Utc::now()).await?;
+
+            let out = replay(ctx.handler_ctx(), "delivery=1").await?;
+            assert!(out.contains(" 1 (create): processed\n"), "{out}");
+            assert!(get_delivery(&db, "1").await?.unwrap().handled);
+
+            // Replaying it claimed it, so GitHub sending it again is ignored.
+            let again = crate::webhook(
+                crate::EventName::Create,
+                PAYLOAD.to_string(),
+                Some("1"),
+                ctx.handler_ctx(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(again, crate::WebhookOutcome::AlreadyProcessed);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,304 @@
+//! Audit log of the actions the bot performs on GitHub.
+//!
+//! `GithubClient` records every successful mutating `Issue` method call in the
+//! `audit_log` table. The triggering webhook and the handler (or job) that
+//! made the call are tracked in task-local scopes set up by [`with_event`] and
+//! [`with_handler`], so handlers don't need to pass anything along. Entries
+//! are written by a background task, so that mutations don't wait for a
+//! database connection.
+//!
+//! The log can be browsed at `/audit?repo=owner/name&issue=123`, as HTML or,
+//! with `format=json` or an `Accept: application/json` header, as JSON.
+
+use crate::db::audit::{get_actions, record_action, AuditEntry};
+use crate::db::ClientPool;
+use crate::github::Issue;
+use chrono::Utc;
+use std::future::Future;
+use tokio::sync::mpsc;
+use tokio_postgres::Client as DbClient;
+use tracing as log;
+
+/// How many entries `/audit` shows at most.
+const AUDIT_PAGE_LIMIT: i64 = 200;
+
+#[derive(Clone)]
+struct EventScope {
+    event: String,
+    delivery_id: Option<String>,
+}
+
+tokio::task_local! {
+    static EVENT: EventScope;
+    static HANDLER: String;
+}
+
+/// Attributes the actions performed by `fut` to the given webhook event.
+pub async fn with_event<F: Future>(
+    event: String,
+    delivery_id: Option<String>,
+    fut: F,
+) -> F::Output {
+    EVENT.scope(EventScope { event, delivery_id }, fut).await
+}
+
+/// Attributes the actions performed by `fut` to the given handler or job.
+pub async fn with_handler<F: Future>(handler: &str, fut: F) -> F::Output {
+    HANDLER.scope(handler.to_string(), fut).await
+}
+
+/// Builds the audit log entry for a mutation of `issue` in the current scope.
+pub(crate) fn entry(issue: &Issue, kind: &str, args: serde_json::Value) -> AuditEntry {
+    let event = EVENT.try_with(|scope| scope.clone()).ok();
+    AuditEntry {
+        time: Utc::now(),
+        repo: issue.repository().full_repo_name(),
+        issue: issue.number,
+        kind: kind.to_string(),
+        args,
+        event: event.as_ref().map(|scope| scope.event.clone()),
+        delivery_id: event.and_then(|scope| scope.delivery_id),
+        handler: HANDLER.try_with(|handler| handler.clone()).ok(),
+    }
+}
+
+/// Spawns the task writing the entries sent on the returned channel to the
+/// `audit_log` table.
+///
+/// Entries sent while a write is in progress are written with the same
+/// connection once it is done.
+pub(crate) fn spawn_writer(db: ClientPool) -> mpsc::UnboundedSender<AuditEntry> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<AuditEntry>();
+    tokio::task::spawn(async move {
+        while let Some(entry) = rx.recv().await {
+            let client = db.get().await;
+            let mut next = Some(entry);
+            while let Some(entry) = next {
+                if let Err(e) = record_action(&client, &entry).await {
+                    // Not being able to audit an action shouldn't prevent it.
+                    log::error!(
+                        "failed to audit {} on {}#{}: {e:?}",
+                        entry.kind,
+                        entry.repo,
+                        entry.issue
+                    );
+                }
+                next = rx.try_recv().ok();
+            }
+        }
+    });
+    tx
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Format {
+    Html,
+    Json,
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Html => "text/html; charset=utf-8",
+            Format::Json => "application/json",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AuditQuery {
+    pub repo: String,
+    pub issue: Option<u64>,
+    pub format: Format,
+}
+
+impl AuditQuery {
+    /// Parses the query string of an `/audit` request. JSON is returned if
+    /// asked for with `format=json` or the `Accept` header.
+    pub fn parse(query: &str, accept: Option<&str>) -> Result<AuditQuery, String> {
+        let mut repo = None;
+        let mut issue = None;
+        let mut format = match accept {
+            Some(accept) if accept.contains("application/json") => Format::Json,
+            _ => Format::Html,
+        };
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            match &*key {
+                "repo" => repo = Some(value.into_owned()),
+                "issue" => {
+                    issue = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid issue number `{value}`"))?,
+                    )
+                }
+                "format" => {
+                    format = match &*value {
+                        "html" => Format::Html,
+                        "json" => Format::Json,
+                        _ => return Err(format!("unknown format `{value}`")),
+                    }
+                }
+                _ => return Err(format!("unknown query parameter `{key}`")),
+            }
+        }
+        let repo = repo
+            .filter(|repo| repo.contains('/'))
+            .ok_or("Please provide `?repo=<owner>/<name>` (and optionally `&issue=<number>`).")?;
+        Ok(AuditQuery {
+            repo,
+            issue,
+            format,
+        })
+    }
+}
+
+pub async fn render(db: &DbClient, query: &AuditQuery) -> anyhow::Result<String> {
+    let entries = get_actions(db, &query.repo, query.issue, AUDIT_PAGE_LIMIT).await?;
+    Ok(match query.format {
+        Format::Json => serde_json::to_string(&entries)?,
+        Format::Html => render_html(query, &entries),
+    })
+}
+
+fn render_html(query: &AuditQuery, entries: &[AuditEntry]) -> String {
+    let target = match query.issue {
+        Some(issue) => format!("{}#{issue}", query.repo),
+        None => query.repo.clone(),
+    };
+
+    let mut out = String::new();
+    out.push_str("<html>");
+    out.push_str("<head>");
+    out.push_str("<meta charset=\"utf-8\">");
+    out.push_str(&format!(
+        "<title>Triagebot actions on {}</title>",
+        escape(&target)
+    ));
+    out.push_str("</head>");
+    out.push_str("<body>");
+    out.push_str(&format!(
+        "<h3>Triagebot actions on {}</h3>",
+        escape(&target)
+    ));
+
+    if entries.is_empty() {
+        out.push_str("<p><em>No actions recorded.</em></p>");
+    } else {
+        out.push_str("<table>");
+        out.push_str(
+            "<tr><th>Time</th><th>Issue</th><th>Action</th><th>Arguments</th>\
+             <th>Handler</th><th>Event</th><th>Delivery</th></tr>",
+        );
+        for entry in entries {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td><a href=\"https://github.com/{}/issues/{}\">#{}</a></td>\
+                 <td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                entry.time.format("%Y-%m-%d %H:%M:%S UTC"),
+                escape(&entry.repo),
+                entry.issue,
+                entry.issue,
+                escape(&entry.kind),
+                escape(&entry.args.to_string()),
+                escape(entry.handler.as_deref().unwrap_or("")),
+                escape(entry.event.as_deref().unwrap_or("")),
+                escape(entry.delivery_id.as_deref().unwrap_or("")),
+            ));
+        }
+        out.push_str("</table>");
+        if entries.len() as i64 == AUDIT_PAGE_LIMIT {
+            out.push_str(&format!(
+                "<p><em>Only the most recent {AUDIT_PAGE_LIMIT} actions are shown.</em></p>"
+            ));
+        }
+    }
+
+    out.push_str("</body>");
+    out.push_str("</html>");
+
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_query() {
+        assert_eq!(
+            AuditQuery::parse("repo=rust-lang/rust&issue=12", None),
+            Ok(AuditQuery {
+                repo: "rust-lang/rust".to_string(),
+                issue: Some(12),
+                format: Format::Html,
+            })
+        );
+        assert_eq!(
+            AuditQuery::parse("repo=rust-lang%2Frust&format=json", None)
+                .unwrap()
+                .format,
+            Format::Json
+        );
+        assert_eq!(
+            AuditQuery::parse("repo=rust-lang/rust", Some("application/json"))
+                .unwrap()
+                .format,
+            Format::Json
+        );
+        assert!(AuditQuery::parse("", None).is_err());
+        assert!(AuditQuery::parse("repo=rust", None).is_err());
+        assert!(AuditQuery::parse("repo=rust-lang/rust&issue=x", None).is_err());
+        assert!(AuditQuery::parse("repo=rust-lang/rust&format=xml", None).is_err());
+    }
+
+    #[test]
+    fn html_escapes_args() {
+        let query = AuditQuery::parse("repo=rust-lang/rust&issue=1", None).unwrap();
+        let entry = AuditEntry {
+            time: Utc::now(),
+            repo: "rust-lang/rust".to_string(),
+            issue: 1,
+            kind: "post_comment".to_string(),
+            args: serde_json::json!({ "body": "<script>" }),
+            event: Some("issue_comment".to_string()),
+            delivery_id: None,
+            handler: Some("shortcut".to_string()),
+        };
+        let html = render_html(&query, &[entry]);
+        assert!(html.contains("Triagebot actions on rust-lang/rust#1"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<td>shortcut</td>"));
+    }
+
+    #[tokio::test]
+    async fn entry_uses_scopes() {
+        let issue = crate::tests::github::issue().call();
+        let args = serde_json::json!({ "labels": ["A"] });
+
+        let entry = entry(&issue, "add_labels", args.clone());
+        assert_eq!(entry.event, None);
+        assert_eq!(entry.handler, None);
+
+        let entry = with_event(
+            "issues".to_string(),
+            Some("abc".to_string()),
+            with_handler("autolabel", async {
+                super::entry(&issue, "add_labels", args)
+            }),
+        )
+        .await;
+        assert_eq!(entry.event.as_deref(), Some("issues"));
+        assert_eq!(entry.delivery_id.as_deref(), Some("abc"));
+        assert_eq!(entry.handler.as_deref(), Some("autolabel"));
+        assert_eq!(entry.kind, "add_labels");
+    }
+}
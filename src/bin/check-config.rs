@@ -0,0 +1,53 @@
+use anyhow::Context as _;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use triagebot::config;
+
+/// Checks a `triagebot.toml` locally, with the checks triagebot runs on pull
+/// requests that change it, except those that need to query GitHub.
+///
+/// Exits with a non-zero status if the file is invalid. With `--schema`,
+/// prints a JSON Schema for `triagebot.toml` instead, which editors such as
+/// VS Code (through taplo) can use for completion and validation.
+#[derive(StructOpt, Debug)]
+struct Opt {
+    /// Print the JSON Schema for `triagebot.toml` and exit.
+    #[structopt(long)]
+    schema: bool,
+
+    /// The configuration file to check.
+    #[structopt(parse(from_os_str), default_value = "triagebot.toml")]
+    path: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt = Opt::from_args();
+
+    if opt.schema {
+        println!("{:#}", config::json_schema());
+        return Ok(());
+    }
+
+    let contents = std::fs::read_to_string(&opt.path)
+        .with_context(|| format!("failed to read {}", opt.path.display()))?;
+    match config::check_config(&contents) {
+        Ok(problems) if problems.is_empty() => {
+            println!("{}: ok", opt.path.display());
+            Ok(())
+        }
+        Ok(problems) => {
+            for problem in problems {
+                eprintln!("{}: error {problem}", opt.path.display());
+            }
+            std::process::exit(1);
+        }
+        Err(e) => {
+            let location = match e.position {
+                Some((line, col)) => format!("{}:{line}:{col}", opt.path.display()),
+                None => opt.path.display().to_string(),
+            };
+            eprintln!("{location}: error {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Context as _;
+use structopt::StructOpt;
+
+/// Re-runs the handlers for webhook deliveries recorded by a triagebot server.
+///
+/// Authenticates with the `TRIAGEBOT_ADMIN_TOKEN` environment variable.
+#[derive(StructOpt, Debug)]
+struct Opt {
+    /// The base URL of the triagebot server.
+    #[structopt(long, default_value = "http://localhost:8000")]
+    url: String,
+
+    /// The `X-GitHub-Delivery` id of a single delivery to replay.
+    #[structopt(long, required_unless = "since", conflicts_with = "since")]
+    delivery: Option<String>,
+
+    /// Replay all deliveries received at or after this RFC 3339 timestamp.
+    #[structopt(long)]
+    since: Option<String>,
+
+    /// Only replay deliveries received before this RFC 3339 timestamp
+    /// (defaults to now).
+    #[structopt(long, requires = "since")]
+    until: Option<String>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let opt = Opt::from_args();
+    let token = std::env::var("TRIAGEBOT_ADMIN_TOKEN").context("TRIAGEBOT_ADMIN_TOKEN not set")?;
+
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    if let Some(delivery) = &opt.delivery {
+        query.append_pair("delivery", delivery);
+    }
+    if let Some(since) = &opt.since {
+        query.append_pair("since", since);
+    }
+    if let Some(until) = &opt.until {
+        query.append_pair("until", until);
+    }
+
+    let url = format!(
+        "{}/admin/replay?{}",
+        opt.url.trim_end_matches('/'),
+        query.finish()
+    );
+    let resp = reqwest::Client::new()
+        .post(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .with_context(|| format!("failed to send replay request to {url}"))?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        anyhow::bail!("replay failed ({status}): {body}");
+    }
+    print!("{body}");
+
+    Ok(())
+}
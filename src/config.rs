@@ -2,25 +2,37 @@ use crate::changelogs::ChangelogFormat;
 use crate::github::{GithubClient, Repository};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::sync::{Arc, LazyLock, RwLock};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
 use std::time::{Duration, Instant};
 use tracing as log;
 
 pub(crate) static CONFIG_FILE_NAME: &str = "triagebot.toml";
-const REFRESH_EVERY: Duration = Duration::from_secs(2 * 60); // Every two minutes
-
-static CONFIG_CACHE: LazyLock<
-    RwLock<HashMap<String, (Result<Arc<Config>, ConfigurationError>, Instant)>>,
-> = LazyLock::new(|| RwLock::new(HashMap::new()));
+// Cached configurations are invalidated by pushes changing them (see
+// `invalidate`), so this only matters if a push event is missed.
+const REFRESH_EVERY: Duration = Duration::from_secs(60 * 60); // Every hour
+
+static CONFIG_CACHE: LazyLock<RwLock<HashMap<String, CachedConfig>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+struct CachedConfig {
+    config: Result<Arc<Config>, ConfigurationError>,
+    fetch_time: Instant,
+    /// The files the configuration was read from, as `owner/repo:path`,
+    /// including the bases named by `extends`.
+    sources: Vec<String>,
+}
 
 // This struct maps each possible option of the triagebot.toml.
 // See documentation of options at: https://forge.rust-lang.org/triagebot/pr-assignment.html#configuration
 // When adding a new config option to the triagebot.toml, it must be also mapped here
 // Will be used by the `issue_handlers!()` or `command_handlers!()` macros.
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct Config {
+    /// A base configuration this one inherits from, as `owner/repo` or
+    /// `owner/repo:path/to/file.toml`. See [`resolve_extends`].
+    pub(crate) extends: Option<String>,
     pub(crate) relabel: Option<RelabelConfig>,
     pub(crate) assign: Option<AssignConfig>,
     pub(crate) ping: Option<PingConfig>,
@@ -29,6 +41,9 @@ pub(crate) struct Config {
     pub(crate) major_change: Option<MajorChangeConfig>,
     pub(crate) glacier: Option<GlacierConfig>,
     pub(crate) close: Option<CloseConfig>,
+    pub(crate) reopen: Option<ReopenConfig>,
+    pub(crate) lock: Option<LockConfig>,
+    pub(crate) milestone: Option<MilestoneConfig>,
     pub(crate) autolabel: Option<AutolabelConfig>,
     pub(crate) notify_zulip: Option<NotifyZulipConfig>,
     pub(crate) github_releases: Option<GitHubReleasesConfig>,
@@ -43,26 +58,30 @@ pub(crate) struct Config {
     pub(crate) validate_config: Option<ValidateConfig>,
     pub(crate) pr_tracking: Option<ReviewPrefsConfig>,
     pub(crate) transfer: Option<TransferConfig>,
+    pub(crate) remind: Option<RemindConfig>,
     pub(crate) merge_conflicts: Option<MergeConflictConfig>,
     pub(crate) bot_pull_requests: Option<BotPullRequests>,
     pub(crate) rendered_link: Option<RenderedLinkConfig>,
     pub(crate) canonicalize_issue_links: Option<CanonicalizeIssueLinksConfig>,
     pub(crate) no_mentions: Option<NoMentionsConfig>,
+    pub(crate) ci_failures: Option<CiFailuresConfig>,
+    pub(crate) messages: Option<MessagesConfig>,
+    pub(crate) commands: Option<CommandsConfig>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NominateConfig {
     // team name -> label
     pub(crate) teams: HashMap<String, String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct PingConfig {
     // team name -> message
     // message will have the cc string appended
     #[serde(flatten)]
-    teams: HashMap<String, PingTeamConfig>,
+    pub(crate) teams: HashMap<String, PingTeamConfig>,
 }
 
 impl PingConfig {
@@ -81,7 +100,7 @@ impl PingConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct PingTeamConfig {
     pub(crate) message: String,
@@ -90,7 +109,7 @@ pub(crate) struct PingTeamConfig {
     pub(crate) label: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AssignConfig {
     /// If enabled, then posts a warning comment if the PR is opened against a
@@ -120,7 +139,7 @@ impl AssignConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 #[serde(untagged)]
 pub(crate) enum WarnNonDefaultBranchConfig {
@@ -133,7 +152,7 @@ pub(crate) enum WarnNonDefaultBranchConfig {
     },
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct WarnNonDefaultBranchException {
     /// Substring in the title that match this exception
@@ -159,7 +178,7 @@ impl WarnNonDefaultBranchConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NoMergesConfig {
     /// No action will be taken on PRs with these substrings in the title.
@@ -176,20 +195,21 @@ pub(crate) struct NoMergesConfig {
     pub(crate) message: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NoteConfig {
     #[serde(default)]
+    #[schemars(skip)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct MentionsConfig {
     #[serde(flatten)]
     pub(crate) paths: HashMap<String, MentionsPathConfig>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MentionsPathConfig {
     pub(crate) message: Option<String>,
@@ -197,7 +217,7 @@ pub(crate) struct MentionsPathConfig {
     pub(crate) cc: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RelabelConfig {
@@ -205,20 +225,21 @@ pub(crate) struct RelabelConfig {
     pub(crate) allow_unauthenticated: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ShortcutConfig {
     #[serde(default)]
+    #[schemars(skip)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct PrioritizeConfig {
     pub(crate) label: String,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct ValidateConfig {}
 
 impl ValidateConfig {
@@ -227,7 +248,24 @@ impl ValidateConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+/// How commands can be written, besides `@bot <command>`.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CommandsConfig {
+    /// Lines starting with this are commands as well, e.g. with `/` both
+    /// `@bot label +bug` and `/label +bug` work.
+    pub(crate) prefix: Option<String>,
+}
+
+impl Config {
+    /// The command prefix configured in `[commands]`, if any.
+    pub(crate) fn command_prefix(&self) -> Option<&str> {
+        self.commands.as_ref()?.prefix.as_deref()
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct AutolabelConfig {
     #[serde(flatten)]
     pub(crate) labels: HashMap<String, AutolabelLabelConfig>,
@@ -245,7 +283,7 @@ impl AutolabelConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct AutolabelLabelConfig {
     #[serde(default)]
@@ -260,42 +298,46 @@ pub(crate) struct AutolabelLabelConfig {
     pub(crate) new_issue: bool,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct NotifyZulipConfig {
     #[serde(flatten)]
     pub(crate) labels: HashMap<String, NotifyZulipLabelConfig>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NotifyZulipLabelConfig {
     pub(crate) zulip_stream: u64,
     pub(crate) topic: String,
     #[serde(rename = "message_on_add", default, deserialize_with = "string_or_seq")]
+    #[schemars(with = "StringOrSeq")]
     pub(crate) messages_on_add: Vec<String>,
     #[serde(
         rename = "message_on_remove",
         default,
         deserialize_with = "string_or_seq"
     )]
+    #[schemars(with = "StringOrSeq")]
     pub(crate) messages_on_remove: Vec<String>,
     #[serde(
         rename = "message_on_close",
         default,
         deserialize_with = "string_or_seq"
     )]
+    #[schemars(with = "StringOrSeq")]
     pub(crate) messages_on_close: Vec<String>,
     #[serde(
         rename = "message_on_reopen",
         default,
         deserialize_with = "string_or_seq"
     )]
+    #[schemars(with = "StringOrSeq")]
     pub(crate) messages_on_reopen: Vec<String>,
     #[serde(default)]
     pub(crate) required_labels: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MajorChangeConfig {
     /// A username (typically a group, e.g. T-lang) to ping on Zulip for newly
@@ -331,22 +373,44 @@ impl MajorChangeConfig {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct GlacierConfig {}
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct CloseConfig {}
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReopenConfig {}
+
+/// Enables both `lock` and `unlock`.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LockConfig {}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MilestoneConfig {
+    /// Creates the milestone if there is none with the given title, instead
+    /// of refusing to set it.
+    #[serde(default)]
+    pub(crate) create_missing: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReviewSubmittedConfig {
     pub(crate) review_labels: Vec<String>,
     pub(crate) reviewed_label: String,
+    /// The label replacing `review_labels` when an assignee approves the PR.
+    /// Approvals don't change labels if this is unset.
+    pub(crate) approved_label: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReviewRequestedConfig {
     pub(crate) remove_labels: Vec<String>,
@@ -362,16 +426,53 @@ pub(crate) async fn get(
         config
     } else {
         log::trace!("fetching fresh config for {}", repo.full_name);
-        let res = get_fresh_config(gh, repo).await;
-        CONFIG_CACHE
-            .write()
-            .unwrap()
-            .insert(repo.full_name.to_string(), (res.clone(), Instant::now()));
+        let (res, sources) = get_fresh_config(gh, repo).await;
+        CONFIG_CACHE.write().unwrap().insert(
+            repo.full_name.to_string(),
+            CachedConfig {
+                config: res.clone(),
+                fetch_time: Instant::now(),
+                sources,
+            },
+        );
         res
     }
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+/// Drops the cached configurations read from files in `repo` for which
+/// `changed` returns true, so that they are fetched again when next needed.
+///
+/// This is called for pushes to the default branch, which is where
+/// configurations (and their bases) are read from.
+pub(crate) fn invalidate(repo: &str, changed: impl Fn(&str) -> bool) {
+    CONFIG_CACHE.write().unwrap().retain(|cached_repo, cached| {
+        let stale = cached.sources.iter().any(|source| {
+            source
+                .split_once(':')
+                .is_some_and(|(source_repo, path)| source_repo == repo && changed(path))
+        });
+        if stale {
+            log::info!("invalidating cached config for {cached_repo}");
+        }
+        !stale
+    });
+}
+
+/// Drops the cached configuration of `repo` and fetches it again, returning
+/// a summary of the result for the user who asked for it.
+pub(crate) async fn refresh(gh: &GithubClient, repo: &str) -> anyhow::Result<String> {
+    let repo = gh.repository(repo).await?;
+    CONFIG_CACHE.write().unwrap().remove(&repo.full_name);
+    Ok(match get(gh, &repo).await {
+        Ok(_) => format!("Reloaded the configuration of {}.", repo.full_name),
+        Err(e) => format!(
+            "Reloaded the configuration of {}, which has an error: {e}",
+            repo.full_name
+        ),
+    })
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct GitHubReleasesConfig {
@@ -381,18 +482,29 @@ pub(crate) struct GitHubReleasesConfig {
     pub(crate) changelog_branch: String,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 pub(crate) struct ReviewPrefsConfig {
     #[serde(default)]
+    #[schemars(skip)]
     _empty: (),
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct TransferConfig {}
 
-#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RemindConfig {
+    /// Sends reminders as Zulip direct messages to users with a known Zulip
+    /// account, instead of commenting on the issue.
+    #[serde(default)]
+    pub(crate) zulip: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct MergeConflictConfig {
@@ -404,59 +516,425 @@ pub(crate) struct MergeConflictConfig {
     pub unless: HashSet<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct BotPullRequests {}
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct RenderedLinkConfig {
     pub(crate) trigger_files: Vec<String>,
 }
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct CanonicalizeIssueLinksConfig {}
 
-#[derive(PartialEq, Eq, Debug, serde::Deserialize)]
+/// Relabels PRs (e.g. from `S-waiting-on-review` to `S-waiting-on-author`)
+/// when their CI fails.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CiFailuresConfig {
+    /// The names of the checks whose failure triggers the relabel. If empty,
+    /// any failing check does.
+    #[serde(default)]
+    pub(crate) required_checks: Vec<String>,
+    /// Labels to remove. If non-empty, only PRs with at least one of these
+    /// labels are relabeled, so the PR is only updated once per failure.
+    #[serde(default)]
+    pub(crate) remove: Vec<String>,
+    /// Labels to add.
+    #[serde(default)]
+    pub(crate) add: Vec<String>,
+    /// Whether to post a comment listing the failing checks.
+    #[serde(default = "CiFailuresConfig::comment_default")]
+    pub(crate) comment: bool,
+}
+
+impl CiFailuresConfig {
+    fn comment_default() -> bool {
+        true
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub(crate) struct NoMentionsConfig {}
 
+/// Replacements for the messages the bot posts, as
+/// [Tera](https://keats.github.io/tera/docs/#templates) templates. Unset
+/// messages use the built-in text.
+#[derive(PartialEq, Eq, Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MessagesConfig {
+    /// Welcome for a PR from a first-time contributor. Variables: `author`,
+    /// `bot`, `assignee` (unset if no reviewer was found) and
+    /// `contributing_url` (unset if not configured in `[assign]`).
+    pub(crate) new_user_welcome: Option<String>,
+    /// Welcome for a PR from a returning contributor. Variables: `author`,
+    /// `bot` and `assignee` (unset if no reviewer was found).
+    pub(crate) returning_user_welcome: Option<String>,
+    /// Posted when the requested reviewer is on vacation. Variables:
+    /// `username` and `bot`.
+    pub(crate) on_vacation: Option<String>,
+    /// Posted when the requested reviewer has no review capacity. Variables:
+    /// `username` and `bot`.
+    pub(crate) reviewer_has_no_capacity: Option<String>,
+    /// Posted when no candidate reviewer has review capacity. Variables:
+    /// `bot`.
+    pub(crate) no_reviewer_has_capacity: Option<String>,
+    /// Reminds the author how to mark the PR ready after `@bot author`.
+    /// Variables: `author` and `bot`.
+    pub(crate) author_reminder: Option<String>,
+    /// Wraps every error the bot reports. Variables: `message`.
+    pub(crate) error_comment: Option<String>,
+}
+
 fn get_cached_config(repo: &str) -> Option<Result<Arc<Config>, ConfigurationError>> {
     let cache = CONFIG_CACHE.read().unwrap();
-    cache.get(repo).and_then(|(config, fetch_time)| {
-        if fetch_time.elapsed() < REFRESH_EVERY {
-            Some(config.clone())
+    cache.get(repo).and_then(|cached| {
+        if cached.fetch_time.elapsed() < REFRESH_EVERY {
+            Some(cached.config.clone())
         } else {
             None
         }
     })
 }
 
+/// Fetches the configuration of `repo`, also returning the files it was read
+/// from (see [`CachedConfig::sources`]).
 async fn get_fresh_config(
     gh: &GithubClient,
     repo: &Repository,
+) -> (Result<Arc<Config>, ConfigurationError>, Vec<String>) {
+    let sources = Mutex::new(vec![format!("{}:{CONFIG_FILE_NAME}", repo.full_name)]);
+    let res = fetch_config(gh, repo, &sources).await;
+    (res, sources.into_inner().unwrap())
+}
+
+async fn fetch_config(
+    gh: &GithubClient,
+    repo: &Repository,
+    sources: &Mutex<Vec<String>>,
 ) -> Result<Arc<Config>, ConfigurationError> {
+    // Configurations are read through the API rather than from
+    // raw.githubusercontent.com, whose CDN would keep serving the file from
+    // before a push that invalidated the cached configuration.
     let contents = gh
-        .raw_file(&repo.full_name, &repo.default_branch, CONFIG_FILE_NAME)
+        .file_contents(&repo.full_name, CONFIG_FILE_NAME)
         .await
         .map_err(|e| ConfigurationError::Http(Arc::new(e)))?
         .ok_or(ConfigurationError::Missing)?;
     let contents = String::from_utf8_lossy(&*contents);
-    let config = Arc::new(toml::from_str::<Config>(&contents).map_err(ConfigurationError::Toml)?);
+    let table = toml::from_str::<toml::Table>(&contents).map_err(ConfigurationError::Toml)?;
+    let table = resolve_extends(&repo.full_name, table, |repo, path| {
+        // Recorded before fetching, so that fixing a missing base also
+        // invalidates the cached error.
+        sources.lock().unwrap().push(format!("{repo}:{path}"));
+        async move {
+            // Base configurations are read from the default branch of their repo.
+            gh.file_contents(&repo, &path).await
+        }
+    })
+    .await?;
+    let config = Arc::new(
+        toml::Value::Table(table)
+            .try_into::<Config>()
+            .map_err(ConfigurationError::Toml)?,
+    );
     log::debug!("fresh configuration for {}: {:?}", repo.full_name, config);
     Ok(config)
 }
 
+/// Applies the `extends` key of a configuration.
+///
+/// The base configuration is fetched (following its own `extends`, if any)
+/// and the local configuration is merged on top of it: tables are merged key
+/// by key, while any other value in the local configuration replaces the base
+/// one. `fetch` returns the contents of a file given a repository and a path.
+async fn resolve_extends<F, Fut>(
+    repo: &str,
+    local: toml::Table,
+    fetch: F,
+) -> Result<toml::Table, ConfigurationError>
+where
+    F: Fn(String, String) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<Option<bytes::Bytes>>>,
+{
+    let mut chain = vec![format!("{repo}:{CONFIG_FILE_NAME}")];
+    // The local configuration followed by its bases, outermost last.
+    let mut layers = vec![local];
+    while let Some(extends) = layers.last().unwrap().get(EXTENDS_KEY) {
+        let (base_repo, base_path) = parse_extends(extends, chain.last().unwrap())?;
+        let base = format!("{base_repo}:{base_path}");
+        if chain.contains(&base) {
+            chain.push(base);
+            return Err(ConfigurationError::ExtendsCycle(chain));
+        }
+        let base_error = |error| ConfigurationError::Extends {
+            base: base.clone(),
+            error: Arc::new(error),
+        };
+        let contents = fetch(base_repo, base_path)
+            .await
+            .map_err(|e| base_error(ConfigurationError::Http(Arc::new(e))))?
+            .ok_or_else(|| base_error(ConfigurationError::Missing))?;
+        let table = toml::from_str::<toml::Table>(&String::from_utf8_lossy(&contents))
+            .map_err(|e| base_error(ConfigurationError::Toml(e)))?;
+        log::trace!("{} extends {base}", chain.last().unwrap());
+        chain.push(base);
+        layers.push(table);
+    }
+
+    let mut layers = layers.into_iter().rev();
+    let mut merged = layers.next().unwrap();
+    for layer in layers {
+        // Only the local `extends` is kept, as the one relevant to users.
+        merged.remove(EXTENDS_KEY);
+        merge_tables(&mut merged, layer);
+    }
+    Ok(merged)
+}
+
+/// The key of a configuration's base configuration.
+const EXTENDS_KEY: &str = "extends";
+
+/// Parses an `extends` value into a repository and a path in it. `source` is
+/// the configuration containing the value, for error messages.
+fn parse_extends(
+    value: &toml::Value,
+    source: &str,
+) -> Result<(String, String), ConfigurationError> {
+    let invalid = || {
+        ConfigurationError::ExtendsInvalid(format!(
+            "`{EXTENDS_KEY}` in `{source}` must be a string of the form \
+             `owner/repo` or `owner/repo:path`, found `{value}`"
+        ))
+    };
+    let value = value.as_str().ok_or_else(invalid)?;
+    let (repo, path) = value.split_once(':').unwrap_or((value, CONFIG_FILE_NAME));
+    match repo.split_once('/') {
+        Some((owner, name))
+            if !owner.is_empty() && !name.is_empty() && !name.contains('/') && !path.is_empty() =>
+        {
+            Ok((repo.to_string(), path.to_string()))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Merges `overlay` into `base`, recursing into tables present in both.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// An error found in a `triagebot.toml` by [`check_config`].
+#[derive(Debug)]
+pub struct ConfigCheckError {
+    /// The path to the offending key, e.g. `assign.owners`.
+    pub path: String,
+    /// The 1-based `(line, column)` of the error, if known.
+    pub position: Option<(usize, usize)>,
+    pub error: toml::de::Error,
+}
+
+impl std::error::Error for ConfigCheckError {}
+
+impl fmt::Display for ConfigCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.error)
+    }
+}
+
+/// Checks that `contents` is a valid `triagebot.toml`, returning the
+/// [`config_problems`] of a file that parses.
+///
+/// This only checks the file itself: a base configuration named by `extends`
+/// is not loaded, and the checks that need GitHub (such as whether labels
+/// exist) are only done on pull requests, by the `validate_config` handler.
+pub fn check_config(contents: &str) -> Result<Vec<String>, ConfigCheckError> {
+    parse_config(contents).map(|config| config_problems(&config))
+}
+
+/// Like [`check_config`], but returns the parsed configuration.
+pub(crate) fn parse_config(contents: &str) -> Result<Config, ConfigCheckError> {
+    let deserializer = toml::Deserializer::new(contents);
+    match serde_path_to_error::deserialize::<_, Config>(deserializer) {
+        Ok(config) => Ok(config),
+        Err(e) => {
+            let path = e.path().to_string();
+            let error = e.into_inner();
+            let position = match error.span() {
+                // toml sometimes gives bad spans, see https://github.com/toml-rs/toml/issues/589
+                Some(span) if span != (0..0) => Some(translate_position(contents, span.start)),
+                Some(_) | None => None,
+            };
+            Err(ConfigCheckError {
+                path,
+                position,
+                error,
+            })
+        }
+    }
+}
+
+/// Returns the problems in a configuration that parses: patterns and message
+/// templates that don't compile, fields matched as path prefixes that look
+/// like globs, and an invalid command prefix.
+pub(crate) fn config_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(assign) = &config.assign {
+        for pattern in sorted(assign.owners.keys()) {
+            if let Err(e) = owner_matcher(pattern) {
+                problems.push(format!(
+                    "`[assign.owners]`: `{pattern}` is not a valid pattern: {e}"
+                ));
+            }
+        }
+    }
+    if let Some(autolabel) = &config.autolabel {
+        for label in sorted(autolabel.labels.keys()) {
+            let cfg = &autolabel.labels[label];
+            for pattern in &cfg.exclude_labels {
+                if let Err(e) = glob::Pattern::new(pattern) {
+                    problems.push(format!(
+                        "`[autolabel.\"{label}\"]`: `exclude_labels` pattern `{pattern}` is not valid: {e}"
+                    ));
+                }
+            }
+            for path in &cfg.trigger_files {
+                if is_glob(path) {
+                    problems.push(format!(
+                        "`[autolabel.\"{label}\"]`: `trigger_files` entry `{path}` looks like a glob, \
+                         but `trigger_files` are matched as path prefixes"
+                    ));
+                }
+            }
+        }
+    }
+    if let Some(notify_zulip) = &config.notify_zulip {
+        for label in sorted(notify_zulip.labels.keys()) {
+            for pattern in &notify_zulip.labels[label].required_labels {
+                if let Err(e) = glob::Pattern::new(pattern) {
+                    problems.push(format!(
+                        "`[notify-zulip.\"{label}\"]`: `required_labels` pattern `{pattern}` is not valid: {e}"
+                    ));
+                }
+            }
+        }
+    }
+    if let Some(messages) = &config.messages {
+        for (name, error) in crate::messages::template_errors(messages) {
+            problems.push(format!(
+                "`[messages]`: `{name}` is not a valid template: {error}"
+            ));
+        }
+    }
+    if let Some(mentions) = &config.mentions {
+        for path in sorted(mentions.paths.keys()) {
+            if is_glob(path) {
+                problems.push(format!(
+                    "`[mentions.\"{path}\"]` looks like a glob, \
+                     but `mentions` entries are matched as path prefixes"
+                ));
+            }
+        }
+    }
+    if let Some(prefix) = config.command_prefix() {
+        if prefix.is_empty() || prefix.contains(char::is_whitespace) {
+            problems.push(format!(
+                "`[commands]`: the prefix `{prefix}` must be non-empty and without whitespace"
+            ));
+        }
+    }
+    problems
+}
+
+pub(crate) fn owner_matcher(pattern: &str) -> Result<ignore::gitignore::Gitignore, ignore::Error> {
+    ignore::gitignore::GitignoreBuilder::new("/")
+        .add_line(None, pattern)?
+        .build()
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+pub(crate) fn sorted<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut keys: Vec<_> = keys.collect();
+    keys.sort();
+    keys
+}
+
+/// Returns a JSON Schema describing `triagebot.toml`, for editor support.
+pub fn json_schema() -> serde_json::Value {
+    let mut schema = schemars::schema_for!(Config);
+    let metadata = schema.schema.metadata();
+    metadata.title = Some(CONFIG_FILE_NAME.to_string());
+    metadata.description = Some("Configuration of triagebot for a repository.".to_string());
+    serde_json::to_value(schema).unwrap()
+}
+
+/// Helper to translate a toml span to a `(line_no, col_no)` (1-based).
+fn translate_position(input: &str, index: usize) -> (usize, usize) {
+    if input.is_empty() {
+        return (0, index);
+    }
+
+    let safe_index = index.min(input.len() - 1);
+    let column_offset = index - safe_index;
+
+    let nl = input[0..safe_index]
+        .as_bytes()
+        .iter()
+        .rev()
+        .enumerate()
+        .find(|(_, b)| **b == b'\n')
+        .map(|(nl, _)| safe_index - nl - 1);
+    let line_start = match nl {
+        Some(nl) => nl + 1,
+        None => 0,
+    };
+    let line = input[0..line_start]
+        .as_bytes()
+        .iter()
+        .filter(|c| **c == b'\n')
+        .count();
+    let column = input[line_start..=safe_index].chars().count() - 1;
+    let column = column + column_offset;
+
+    (line + 1, column + 1)
+}
+
 #[derive(Clone, Debug)]
 pub enum ConfigurationError {
     Missing,
     Toml(toml::de::Error),
     Http(Arc<anyhow::Error>),
+    /// The base configuration `base` named by `extends` could not be loaded.
+    Extends {
+        base: String,
+        error: Arc<ConfigurationError>,
+    },
+    /// An `extends` value is malformed.
+    ExtendsInvalid(String),
+    /// The chain of `extends` loops back on itself.
+    ExtendsCycle(Vec<String>),
 }
 
 impl std::error::Error for ConfigurationError {}
@@ -478,10 +956,40 @@ impl fmt::Display for ConfigurationError {
                     "Failed to query configuration for this repository.\n{e:?}"
                 )
             }
+            ConfigurationError::Extends { base, error } => match &**error {
+                ConfigurationError::Missing => write!(
+                    f,
+                    "The base configuration `{base}` extended by `triagebot.toml` does not exist."
+                ),
+                ConfigurationError::Toml(e) => {
+                    write!(f, "Malformed base configuration `{base}`.\n{e}")
+                }
+                ConfigurationError::Http(e) => {
+                    write!(f, "Failed to fetch the base configuration `{base}`.\n{e:?}")
+                }
+                error => write!(f, "{error}"),
+            },
+            ConfigurationError::ExtendsInvalid(message) => {
+                write!(f, "Invalid `triagebot.toml` inheritance.\n{message}")
+            }
+            ConfigurationError::ExtendsCycle(chain) => write!(
+                f,
+                "The `extends` chain of `triagebot.toml` loops back on itself: {}",
+                chain.join(" -> ")
+            ),
         }
     }
 }
 
+/// The shape accepted by [`string_or_seq`], for the JSON Schema.
+#[allow(dead_code)]
+#[derive(schemars::JsonSchema)]
+#[serde(untagged)]
+enum StringOrSeq {
+    String(String),
+    Seq(Vec<String>),
+}
+
 fn string_or_seq<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -553,6 +1061,17 @@ mod tests {
             trigger-files = ["posts/"]
 
             [no-mentions]
+
+            [ci-failures]
+            required-checks = ["PR - x86_64-gnu"]
+            remove = ["S-waiting-on-review"]
+            add = ["S-waiting-on-author"]
+
+            [messages]
+            author-reminder = "@{{ author }}, use `@{{ bot }} ready` when done."
+
+            [commands]
+            prefix = "/"
         "#;
         let config = toml::from_str::<Config>(&config).unwrap();
         let mut ping_teams = HashMap::new();
@@ -580,6 +1099,7 @@ mod tests {
         assert_eq!(
             config,
             Config {
+                extends: None,
                 relabel: Some(RelabelConfig {
                     allow_unauthenticated: vec!["C-*".into()],
                 }),
@@ -600,6 +1120,9 @@ mod tests {
                 major_change: None,
                 glacier: None,
                 close: None,
+                reopen: None,
+                lock: None,
+                milestone: None,
                 autolabel: None,
                 notify_zulip: None,
                 github_releases: None,
@@ -610,6 +1133,7 @@ mod tests {
                 validate_config: Some(ValidateConfig {}),
                 pr_tracking: None,
                 transfer: None,
+                remind: None,
                 merge_conflicts: None,
                 bot_pull_requests: None,
                 rendered_link: Some(RenderedLinkConfig {
@@ -617,6 +1141,26 @@ mod tests {
                 }),
                 canonicalize_issue_links: Some(CanonicalizeIssueLinksConfig {}),
                 no_mentions: Some(NoMentionsConfig {}),
+                ci_failures: Some(CiFailuresConfig {
+                    required_checks: vec!["PR - x86_64-gnu".to_string()],
+                    remove: vec!["S-waiting-on-review".to_string()],
+                    add: vec!["S-waiting-on-author".to_string()],
+                    comment: true,
+                }),
+                messages: Some(MessagesConfig {
+                    new_user_welcome: None,
+                    returning_user_welcome: None,
+                    on_vacation: None,
+                    reviewer_has_no_capacity: None,
+                    no_reviewer_has_capacity: None,
+                    author_reminder: Some(
+                        "@{{ author }}, use `@{{ bot }} ready` when done.".to_string()
+                    ),
+                    error_comment: None,
+                }),
+                commands: Some(CommandsConfig {
+                    prefix: Some("/".to_string()),
+                }),
             }
         );
     }
@@ -639,6 +1183,7 @@ mod tests {
         assert_eq!(
             config,
             Config {
+                extends: None,
                 relabel: None,
                 assign: Some(AssignConfig {
                     warn_non_default_branch: WarnNonDefaultBranchConfig::Extended {
@@ -667,6 +1212,9 @@ mod tests {
                 major_change: None,
                 glacier: None,
                 close: None,
+                reopen: None,
+                lock: None,
+                milestone: None,
                 autolabel: None,
                 notify_zulip: None,
                 github_releases: None,
@@ -677,12 +1225,248 @@ mod tests {
                 validate_config: Some(ValidateConfig {}),
                 pr_tracking: None,
                 transfer: None,
+                remind: None,
                 merge_conflicts: None,
                 bot_pull_requests: None,
                 rendered_link: None,
                 canonicalize_issue_links: None,
                 no_mentions: None,
+                ci_failures: None,
+                messages: None,
+                commands: None,
             }
         );
     }
+
+    /// Resolves `extends` for `rust-lang/local`, with the given files.
+    async fn resolve(
+        local: &str,
+        files: &[(&str, &str)],
+    ) -> Result<toml::Table, ConfigurationError> {
+        let files: HashMap<String, String> = files
+            .iter()
+            .map(|(name, contents)| (name.to_string(), contents.to_string()))
+            .collect();
+        let local = toml::from_str(local).unwrap();
+        resolve_extends("rust-lang/local", local, |repo, path| {
+            let file = files.get(&format!("{repo}:{path}")).cloned();
+            async move { Ok(file.map(bytes::Bytes::from)) }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn extends_deep_merges() {
+        let base = r#"
+            [relabel]
+            allow-unauthenticated = ["C-*", "A-*"]
+
+            [ping.compiler]
+            message = "base"
+            label = "T-compiler"
+
+            [shortcut]
+        "#;
+        let local = r#"
+            extends = "rust-lang/base"
+
+            [relabel]
+            allow-unauthenticated = ["S-*"]
+
+            [ping.compiler]
+            message = "local"
+
+            [ping.release]
+            message = "release"
+        "#;
+        let table = resolve(local, &[("rust-lang/base:triagebot.toml", base)])
+            .await
+            .unwrap();
+        let config = toml::Value::Table(table).try_into::<Config>().unwrap();
+        assert_eq!(config.extends.as_deref(), Some("rust-lang/base"));
+        assert_eq!(
+            config.relabel.unwrap().allow_unauthenticated,
+            vec!["S-*".to_string()]
+        );
+        let ping = config.ping.unwrap();
+        let (_, compiler) = ping.get_by_name("compiler").unwrap();
+        assert_eq!(compiler.message, "local");
+        assert_eq!(compiler.label.as_deref(), Some("T-compiler"));
+        assert!(ping.get_by_name("release").is_some());
+        assert!(config.shortcut.is_some());
+    }
+
+    #[tokio::test]
+    async fn extends_chain_with_path() {
+        let root = r#"
+            [shortcut]
+        "#;
+        let middle = r#"
+            extends = "rust-lang/root"
+            [note]
+        "#;
+        let table = resolve(
+            r#"extends = "rust-lang/org:configs/base.toml""#,
+            &[
+                ("rust-lang/org:configs/base.toml", middle),
+                ("rust-lang/root:triagebot.toml", root),
+            ],
+        )
+        .await
+        .unwrap();
+        let config = toml::Value::Table(table).try_into::<Config>().unwrap();
+        assert_eq!(
+            config.extends.as_deref(),
+            Some("rust-lang/org:configs/base.toml")
+        );
+        assert!(config.shortcut.is_some());
+        assert!(config.note.is_some());
+    }
+
+    #[tokio::test]
+    async fn extends_errors() {
+        let cycle = resolve(
+            r#"extends = "rust-lang/a""#,
+            &[
+                ("rust-lang/a:triagebot.toml", r#"extends = "rust-lang/b""#),
+                ("rust-lang/b:triagebot.toml", r#"extends = "rust-lang/a""#),
+            ],
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            cycle.to_string(),
+            "The `extends` chain of `triagebot.toml` loops back on itself: \
+             rust-lang/local:triagebot.toml -> rust-lang/a:triagebot.toml -> \
+             rust-lang/b:triagebot.toml -> rust-lang/a:triagebot.toml"
+        );
+
+        let missing = resolve(r#"extends = "rust-lang/base""#, &[])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            missing.to_string(),
+            "The base configuration `rust-lang/base:triagebot.toml` extended by \
+             `triagebot.toml` does not exist."
+        );
+
+        let malformed = resolve(
+            r#"extends = "rust-lang/base""#,
+            &[("rust-lang/base:triagebot.toml", "[shortcut")],
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            malformed,
+            ConfigurationError::Extends { ref error, .. }
+                if matches!(**error, ConfigurationError::Toml(_))
+        ));
+
+        for invalid in [
+            r#"extends = "rust-lang""#,
+            r#"extends = 1"#,
+            r#"extends = "a/b/c""#,
+        ] {
+            assert!(matches!(
+                resolve(invalid, &[]).await.unwrap_err(),
+                ConfigurationError::ExtendsInvalid(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn check_config_reports_location() {
+        let problems =
+            check_config("[shortcut]\n[relabel]\nallow-unauthenticated = [\"C-*\"]\n").unwrap();
+        assert!(problems.is_empty());
+
+        let e = check_config("[shortcut]\n\n[ci-failures]\ncomment = \"yes\"\n").unwrap_err();
+        assert_eq!(e.path, "ci-failures.comment");
+        assert_eq!(e.position.map(|(line, _)| line), Some(4));
+
+        assert!(check_config("[shortcut]\nunknown = 1\n").is_err());
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        let config = parse_config(
+            r#"
+            [assign.owners]
+            "/compiler/**" = ["compiler"]
+            "/library/[" = ["libs"]
+
+            [autolabel."T-compiler"]
+            exclude_labels = ["T-[", "S-*"]
+            trigger_files = ["compiler/*.rs", "compiler/rustc_ast"]
+
+            [notify-zulip."I-prioritize"]
+            zulip_stream = 1
+            topic = "issue {number}"
+            required_labels = ["T-**x"]
+
+            [messages]
+            error-comment = "{% if message %}"
+
+            [mentions."src/*/mod.rs"]
+            [mentions."src/tools"]
+
+            [commands]
+            prefix = "/ "
+            "#,
+        )
+        .unwrap();
+        let problems = config_problems(&config);
+        assert_eq!(problems.len(), 7, "{problems:#?}");
+        assert!(problems[0].starts_with("`[assign.owners]`: `/library/[` is not a valid pattern"));
+        assert!(problems[1].contains("`exclude_labels` pattern `T-[`"));
+        assert!(problems[2].contains("`trigger_files` entry `compiler/*.rs`"));
+        assert!(problems[3].contains("`required_labels` pattern `T-**x`"));
+        assert!(problems[4].starts_with("`[messages]`: `error-comment` is not a valid template"));
+        assert!(problems[5].starts_with("`[mentions.\"src/*/mod.rs\"]`"));
+        assert!(problems[6].starts_with("`[commands]`: the prefix `/ `"));
+    }
+
+    #[test]
+    fn json_schema_describes_config() {
+        let schema = json_schema();
+        assert_eq!(schema["title"], "triagebot.toml");
+        let properties = &schema["properties"];
+        assert!(properties["assign"].is_object());
+        assert!(properties["notify-zulip"].is_object());
+        assert!(properties["extends"].is_object());
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[test]
+    fn invalidate_by_source() {
+        let cache = |repo: &str, sources: &[&str]| {
+            CONFIG_CACHE.write().unwrap().insert(
+                repo.to_string(),
+                CachedConfig {
+                    config: Err(ConfigurationError::Missing),
+                    fetch_time: Instant::now(),
+                    sources: sources.iter().map(|s| s.to_string()).collect(),
+                },
+            );
+        };
+        let cached = |repo: &str| CONFIG_CACHE.read().unwrap().contains_key(repo);
+        cache("invalidate-test/a", &["invalidate-test/a:triagebot.toml"]);
+        cache(
+            "invalidate-test/b",
+            &[
+                "invalidate-test/b:triagebot.toml",
+                "invalidate-test/base:configs/b.toml",
+            ],
+        );
+
+        invalidate("invalidate-test/a", |path| path == "README.md");
+        assert!(cached("invalidate-test/a"));
+
+        invalidate("invalidate-test/a", |path| path == CONFIG_FILE_NAME);
+        assert!(!cached("invalidate-test/a"));
+        assert!(cached("invalidate-test/b"));
+
+        invalidate("invalidate-test/base", |path| path == "configs/b.toml");
+        assert!(!cached("invalidate-test/b"));
+    }
 }
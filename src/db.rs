@@ -1,4 +1,4 @@
-use crate::{db::jobs::*, handlers::Context, jobs::jobs};
+use crate::{db::jobs::*, handlers::Context, jobs::jobs, metrics, shutdown::Shutdown};
 use anyhow::Context as _;
 use chrono::Utc;
 use native_tls::{Certificate, TlsConnector};
@@ -7,6 +7,8 @@ use std::sync::{Arc, LazyLock, Mutex};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_postgres::Client as DbClient;
 
+pub mod audit;
+pub mod deliveries;
 pub mod issue_data;
 pub mod jobs;
 pub mod notifications;
@@ -21,6 +23,7 @@ static CERTIFICATE_PEMS: LazyLock<Vec<u8>> = LazyLock::new(|| {
     resp.bytes().expect("failed to get RDS cert body").to_vec()
 });
 
+#[derive(Clone)]
 pub struct ClientPool {
     connections: Arc<Mutex<Vec<tokio_postgres::Client>>>,
     permits: Arc<Semaphore>,
@@ -65,7 +68,9 @@ impl ClientPool {
     }
 
     pub async fn get(&self) -> PooledClient {
+        let start = std::time::Instant::now();
         let permit = self.permits.clone().acquire_owned().await.unwrap();
+        metrics::record_db_pool_wait(start.elapsed());
         {
             let mut slots = self.connections.lock().unwrap_or_else(|e| e.into_inner());
             // Pop connections until we hit a non-closed connection (or there are no
@@ -232,21 +237,47 @@ pub async fn schedule_job(
     Ok(())
 }
 
-pub async fn run_scheduled_jobs(ctx: &Context) -> anyhow::Result<()> {
+/// Runs the jobs that are due.
+///
+/// Once `shutdown` has started no further jobs are started, and a job still
+/// running at the shutdown deadline is abandoned and marked to be run again.
+pub async fn run_scheduled_jobs(ctx: &Context, shutdown: &Shutdown) -> anyhow::Result<()> {
     let db = &ctx.db.get().await;
     let jobs = get_jobs_to_execute(&db).await?;
     tracing::trace!("jobs to execute: {:#?}", jobs);
 
+    let mut shutdown = shutdown.clone();
     for job in jobs.iter() {
+        if shutdown.is_requested() {
+            tracing::info!("not starting remaining jobs, shutting down");
+            break;
+        }
         update_job_executed_at(&db, &job.id).await?;
 
-        match handle_job(&ctx, &job.name, &job.metadata).await {
+        let result = tokio::select! {
+            result = crate::audit::with_handler(
+                &job.name,
+                handle_job(&ctx, &job.name, &job.metadata),
+            ) => result,
+            _ = shutdown.deadline() => {
+                tracing::warn!(
+                    "shutdown deadline interrupted job (id={}, name={})",
+                    job.id,
+                    job.name
+                );
+                mark_job_interrupted(&db, &job.id).await?;
+                break;
+            }
+        };
+        match result {
             Ok(_) => {
                 tracing::trace!("job successfully executed (id={})", job.id);
+                metrics::record_job_run(&job.name, true);
                 delete_job(&db, &job.id).await?;
             }
             Err(e) => {
                 tracing::error!("job failed on execution (id={:?}, error={:?})", job.id, e);
+                metrics::record_job_run(&job.name, false);
                 update_job_error_message(&db, &job.id, &e.to_string()).await?;
             }
         }
@@ -344,5 +375,37 @@ CREATE UNIQUE INDEX IF NOT EXISTS review_prefs_user_id ON review_prefs(user_id);
  ",
     "
 ALTER TABLE review_prefs ADD COLUMN IF NOT EXISTS max_assigned_prs INTEGER DEFAULT NULL;
+",
+    "
+CREATE TABLE webhook_deliveries (
+    delivery_id TEXT PRIMARY KEY,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    errors TEXT[] NOT NULL DEFAULT array[]::TEXT[]
+);
+",
+    "
+CREATE INDEX webhook_deliveries_received_at_index ON webhook_deliveries (received_at);
+",
+    "
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    time TIMESTAMP WITH TIME ZONE NOT NULL,
+    repo TEXT NOT NULL,
+    issue BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    args JSONB NOT NULL,
+    event TEXT,
+    delivery_id TEXT,
+    handler TEXT
+);
+",
+    "
+CREATE INDEX audit_log_repo_issue_time_index ON audit_log (repo, issue, time);
+",
+    "
+ALTER TABLE webhook_deliveries ADD COLUMN handled BOOLEAN NOT NULL DEFAULT TRUE;
 ",
 ];
@@ -0,0 +1,133 @@
+//! The `audit_log` table records every mutating action the bot performs on
+//! GitHub, so that "why did the bot do this?" can be answered without digging
+//! through logs.
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, serde::Serialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    /// The repository, e.g. `rust-lang/rust`.
+    pub repo: String,
+    pub issue: u64,
+    /// The name of the `Issue` method that was called, e.g. `add_labels`.
+    pub kind: String,
+    pub args: serde_json::Value,
+    /// The name of the event that triggered the action, if any.
+    pub event: Option<String>,
+    /// The `X-GitHub-Delivery` id of the triggering webhook, if any.
+    pub delivery_id: Option<String>,
+    /// The handler or job that performed the action.
+    pub handler: Option<String>,
+}
+
+pub async fn record_action(db: &DbClient, entry: &AuditEntry) -> Result<()> {
+    tracing::trace!(
+        "record_action(repo={}, issue={}, kind={})",
+        entry.repo,
+        entry.issue,
+        entry.kind
+    );
+
+    db.execute(
+        "INSERT INTO audit_log (time, repo, issue, kind, args, event, delivery_id, handler)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        &[
+            &entry.time,
+            &entry.repo,
+            &(entry.issue as i64),
+            &entry.kind,
+            &entry.args,
+            &entry.event,
+            &entry.delivery_id,
+            &entry.handler,
+        ],
+    )
+    .await
+    .context("Inserting audit log entry")?;
+
+    Ok(())
+}
+
+/// Returns the most recent `limit` actions in `repo`, restricted to one issue
+/// if `issue` is given, newest first.
+pub async fn get_actions(
+    db: &DbClient,
+    repo: &str,
+    issue: Option<u64>,
+    limit: i64,
+) -> Result<Vec<AuditEntry>> {
+    let issue = issue.map(|issue| issue as i64);
+    let rows = db
+        .query(
+            "SELECT time, repo, issue, kind, args, event, delivery_id, handler
+            FROM audit_log
+            WHERE repo = $1 AND ($2::BIGINT IS NULL OR issue = $2)
+            ORDER BY time DESC
+            LIMIT $3",
+            &[&repo, &issue, &limit],
+        )
+        .await
+        .context("Getting audit log entries")?;
+
+    rows.iter().map(deserialize_entry).collect()
+}
+
+fn deserialize_entry(row: &tokio_postgres::row::Row) -> Result<AuditEntry> {
+    let issue: i64 = row.try_get(2)?;
+    Ok(AuditEntry {
+        time: row.try_get(0)?,
+        repo: row.try_get(1)?,
+        issue: issue as u64,
+        kind: row.try_get(3)?,
+        args: row.try_get(4)?,
+        event: row.try_get(5)?,
+        delivery_id: row.try_get(6)?,
+        handler: row.try_get(7)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::run_test;
+    use chrono::Duration;
+
+    fn entry(issue: u64, kind: &str, minutes_ago: i64) -> AuditEntry {
+        AuditEntry {
+            time: Utc::now() - Duration::minutes(minutes_ago),
+            repo: "rust-lang/rust".to_string(),
+            issue,
+            kind: kind.to_string(),
+            args: serde_json::json!({ "labels": ["T-compiler"] }),
+            event: Some("issues".to_string()),
+            delivery_id: Some("abc".to_string()),
+            handler: Some("autolabel".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn query_actions() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            record_action(&db, &entry(1, "add_labels", 3)).await?;
+            record_action(&db, &entry(2, "post_comment", 2)).await?;
+            record_action(&db, &entry(1, "remove_label", 1)).await?;
+
+            let actions = get_actions(&db, "rust-lang/rust", Some(1), 10).await?;
+            let kinds: Vec<_> = actions.iter().map(|a| a.kind.as_str()).collect();
+            assert_eq!(kinds, ["remove_label", "add_labels"]);
+            assert_eq!(actions[0].handler.as_deref(), Some("autolabel"));
+            assert_eq!(actions[0].args["labels"][0], "T-compiler");
+
+            assert_eq!(get_actions(&db, "rust-lang/rust", None, 10).await?.len(), 3);
+            assert_eq!(get_actions(&db, "rust-lang/rust", None, 1).await?.len(), 1);
+            assert!(get_actions(&db, "rust-lang/cargo", None, 10)
+                .await?
+                .is_empty());
+            Ok(ctx)
+        })
+        .await;
+    }
+}
@@ -0,0 +1,292 @@
+//! The `webhook_deliveries` table keeps every GitHub webhook delivery that
+//! was accepted, so that it can be replayed later (for example after a
+//! handler bug dropped an event, or a shutdown cut it off), and so that
+//! deliveries GitHub sends again are only handled once.
+use crate::handlers::Context;
+use crate::jobs::Job;
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use tokio_postgres::Client as DbClient;
+
+#[derive(Debug, serde::Serialize)]
+pub struct WebhookDelivery {
+    /// The `X-GitHub-Delivery` id.
+    pub delivery_id: String,
+    /// The `X-GitHub-Event` name.
+    pub event: String,
+    /// The raw JSON payload.
+    pub payload: String,
+    pub received_at: DateTime<Utc>,
+    /// How long the handlers took to process the delivery.
+    pub duration_ms: i32,
+    /// Errors reported by the handlers, if any.
+    pub errors: Vec<String>,
+    /// Whether the handlers were run, which is `false` for a delivery that was
+    /// queued but never handled.
+    pub handled: bool,
+}
+
+/// How long delivery ids are remembered for deduplication.
+///
+/// GitHub only redelivers recent webhooks, so anything older than this is
+/// removed by the `webhook_deliveries_cleanup` job.
+pub const DELIVERY_RETENTION_DAYS: i64 = 14;
+
+/// Records a delivery that was queued but not handled yet, so that it can be
+/// replayed if it never is.
+///
+/// Does nothing if a delivery with the same id was already recorded within
+/// the retention window.
+pub async fn store_delivery(
+    db: &DbClient,
+    delivery_id: &str,
+    event: &str,
+    payload: &str,
+    received_at: &DateTime<Utc>,
+) -> Result<()> {
+    tracing::trace!("store_delivery(delivery_id={})", delivery_id);
+
+    let cutoff = *received_at - Duration::days(DELIVERY_RETENTION_DAYS);
+    db.execute(
+        "INSERT INTO webhook_deliveries (delivery_id, event, payload, received_at, duration_ms, handled)
+        VALUES ($1, $2, $3, $4, 0, FALSE)
+        ON CONFLICT (delivery_id) DO UPDATE
+        SET event = EXCLUDED.event, payload = EXCLUDED.payload, received_at = EXCLUDED.received_at,
+            duration_ms = 0, errors = array[]::TEXT[], handled = FALSE
+        WHERE webhook_deliveries.received_at < $5",
+        &[&delivery_id, &event, &payload, received_at, &cutoff],
+    )
+    .await
+    .context("Storing webhook delivery")?;
+
+    Ok(())
+}
+
+/// Records that a delivery is about to be handled.
+///
+/// Returns `false` if a delivery with the same id was already handled within
+/// the retention window, in which case it must not be handled again. A
+/// delivery that was only stored with [`store_delivery`] can be claimed once.
+pub async fn claim_delivery(
+    db: &DbClient,
+    delivery_id: &str,
+    event: &str,
+    payload: &str,
+    received_at: &DateTime<Utc>,
+) -> Result<bool> {
+    tracing::trace!("claim_delivery(delivery_id={})", delivery_id);
+
+    let cutoff = *received_at - Duration::days(DELIVERY_RETENTION_DAYS);
+    let inserted = db
+        .execute(
+            "INSERT INTO webhook_deliveries (delivery_id, event, payload, received_at, duration_ms, handled)
+            VALUES ($1, $2, $3, $4, 0, TRUE)
+            ON CONFLICT (delivery_id) DO UPDATE
+            SET event = EXCLUDED.event, payload = EXCLUDED.payload, received_at = EXCLUDED.received_at,
+                duration_ms = 0, errors = array[]::TEXT[], handled = TRUE
+            WHERE webhook_deliveries.received_at < $5 OR NOT webhook_deliveries.handled",
+            &[&delivery_id, &event, &payload, received_at, &cutoff],
+        )
+        .await
+        .context("Claiming webhook delivery")?;
+
+    Ok(inserted == 1)
+}
+
+/// Stores the outcome of handling a delivery claimed with `claim_delivery`.
+pub async fn finish_delivery(
+    db: &DbClient,
+    delivery_id: &str,
+    duration_ms: i32,
+    errors: &[String],
+) -> Result<()> {
+    tracing::trace!("finish_delivery(delivery_id={})", delivery_id);
+
+    db.execute(
+        "UPDATE webhook_deliveries SET duration_ms = $2, errors = $3 WHERE delivery_id = $1",
+        &[&delivery_id, &duration_ms, &errors],
+    )
+    .await
+    .context("Updating webhook delivery")?;
+
+    Ok(())
+}
+
+/// Removes deliveries received before `cutoff`, returning how many were removed.
+pub async fn delete_deliveries_before(db: &DbClient, cutoff: &DateTime<Utc>) -> Result<u64> {
+    tracing::trace!("delete_deliveries_before(cutoff={})", cutoff);
+
+    db.execute(
+        "DELETE FROM webhook_deliveries WHERE received_at < $1",
+        &[cutoff],
+    )
+    .await
+    .context("Deleting old webhook deliveries")
+}
+
+pub async fn get_delivery(db: &DbClient, delivery_id: &str) -> Result<Option<WebhookDelivery>> {
+    let row = db
+        .query_opt(
+            "SELECT delivery_id, event, payload, received_at, duration_ms, errors, handled
+            FROM webhook_deliveries WHERE delivery_id = $1",
+            &[&delivery_id],
+        )
+        .await
+        .context("Getting webhook delivery")?;
+
+    row.as_ref().map(deserialize_delivery).transpose()
+}
+
+/// Returns the deliveries received in the `[since, until)` range, oldest first.
+pub async fn get_deliveries_between(
+    db: &DbClient,
+    since: &DateTime<Utc>,
+    until: &DateTime<Utc>,
+) -> Result<Vec<WebhookDelivery>> {
+    let rows = db
+        .query(
+            "SELECT delivery_id, event, payload, received_at, duration_ms, errors, handled
+            FROM webhook_deliveries
+            WHERE received_at >= $1 AND received_at < $2
+            ORDER BY received_at",
+            &[&since, &until],
+        )
+        .await
+        .context("Getting webhook deliveries")?;
+
+    rows.iter().map(deserialize_delivery).collect()
+}
+
+fn deserialize_delivery(row: &tokio_postgres::row::Row) -> Result<WebhookDelivery> {
+    Ok(WebhookDelivery {
+        delivery_id: row.try_get(0)?,
+        event: row.try_get(1)?,
+        payload: row.try_get(2)?,
+        received_at: row.try_get(3)?,
+        duration_ms: row.try_get(4)?,
+        errors: row.try_get(5)?,
+        handled: row.try_get(6)?,
+    })
+}
+
+/// Removes deliveries older than [`DELIVERY_RETENTION_DAYS`].
+pub struct WebhookDeliveriesCleanupJob;
+
+#[async_trait]
+impl Job for WebhookDeliveriesCleanupJob {
+    fn name(&self) -> &'static str {
+        "webhook_deliveries_cleanup"
+    }
+
+    async fn run(&self, ctx: &Context, _metadata: &serde_json::Value) -> Result<()> {
+        let cutoff = Utc::now() - Duration::days(DELIVERY_RETENTION_DAYS);
+        let deleted = delete_deliveries_before(&*ctx.db.get().await, &cutoff).await?;
+        tracing::info!("removed {deleted} webhook deliveries received before {cutoff}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::deliveries::{
+        claim_delivery, finish_delivery, get_delivery, store_delivery, DELIVERY_RETENTION_DAYS,
+    };
+    use crate::tests::run_test;
+    use crate::{webhook, EventName, WebhookOutcome};
+    use chrono::{Duration, Utc};
+
+    const PAYLOAD: &str = r#"{
+        "ref_type": "branch",
+        "repository": {"full_name": "rust-lang/triagebot", "default_branch": "master"},
+        "sender": {"login": "octocat", "id": 1}
+    }"#;
+
+    #[tokio::test]
+    async fn redelivered_webhook_is_handled_once() {
+        run_test(|ctx| async {
+            let handler_ctx = ctx.handler_ctx();
+            let db = ctx.db_client().await;
+
+            let first = webhook(
+                EventName::Create,
+                PAYLOAD.to_string(),
+                Some("1"),
+                handler_ctx,
+            )
+            .await
+            .unwrap();
+            assert_eq!(first, WebhookOutcome::Processed);
+            // Handling a delivery again would claim it anew and overwrite this.
+            finish_delivery(&db, "1", 1234, &["first run".to_string()]).await?;
+
+            let second = webhook(
+                EventName::Create,
+                PAYLOAD.to_string(),
+                Some("1"),
+                handler_ctx,
+            )
+            .await
+            .unwrap();
+            assert_eq!(second, WebhookOutcome::AlreadyProcessed);
+            let delivery = get_delivery(&db, "1").await?.unwrap();
+            assert_eq!(delivery.duration_ms, 1234);
+            assert_eq!(delivery.errors, ["first run"]);
+
+            // A different delivery of the same payload is still handled.
+            let other = webhook(
+                EventName::Create,
+                PAYLOAD.to_string(),
+                Some("2"),
+                handler_ctx,
+            )
+            .await
+            .unwrap();
+            assert_eq!(other, WebhookOutcome::Processed);
+            assert!(get_delivery(&db, "2").await?.is_some());
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn delivery_is_handled_again_after_retention() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            let long_ago = Utc::now() - Duration::days(DELIVERY_RETENTION_DAYS + 1);
+
+            assert!(claim_delivery(&db, "1", "create", PAYLOAD, &long_ago).await?);
+            assert!(!claim_delivery(&db, "1", "create", PAYLOAD, &long_ago).await?);
+
+            let now = Utc::now();
+            assert!(claim_delivery(&db, "1", "create", PAYLOAD, &now).await?);
+            assert_eq!(get_delivery(&db, "1").await?.unwrap().received_at, now);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn stored_delivery_is_claimed_once() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            let now = Utc::now();
+
+            store_delivery(&db, "1", "create", PAYLOAD, &now).await?;
+            assert!(!get_delivery(&db, "1").await?.unwrap().handled);
+            assert!(claim_delivery(&db, "1", "create", PAYLOAD, &now).await?);
+            assert!(get_delivery(&db, "1").await?.unwrap().handled);
+            assert!(!claim_delivery(&db, "1", "create", PAYLOAD, &now).await?);
+
+            // GitHub sending a handled delivery again doesn't make it claimable.
+            store_delivery(&db, "1", "create", PAYLOAD, &now).await?;
+            assert!(get_delivery(&db, "1").await?.unwrap().handled);
+            assert!(!claim_delivery(&db, "1", "create", PAYLOAD, &now).await?);
+
+            Ok(ctx)
+        })
+        .await;
+    }
+}
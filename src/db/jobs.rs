@@ -41,6 +41,28 @@ pub async fn insert_job(
     Ok(())
 }
 
+/// Inserts a job unless one with the same name is already scheduled at the
+/// same time, returning whether it was inserted.
+pub async fn try_insert_job(
+    db: &DbClient,
+    name: &str,
+    scheduled_at: &DateTime<Utc>,
+    metadata: &serde_json::Value,
+) -> Result<bool> {
+    tracing::trace!("try_insert_job(name={})", name);
+
+    let inserted = db
+        .query_opt(
+            "INSERT INTO jobs (name, scheduled_at, metadata) VALUES ($1, $2, $3)
+                ON CONFLICT (name, scheduled_at) DO NOTHING RETURNING id",
+            &[&name, &scheduled_at, &metadata],
+        )
+        .await
+        .context("Inserting job")?;
+
+    Ok(inserted.is_some())
+}
+
 pub async fn delete_job(db: &DbClient, id: &Uuid) -> Result<()> {
     tracing::trace!("delete_job(id={})", id);
 
@@ -74,6 +96,21 @@ pub async fn update_job_executed_at(db: &DbClient, id: &Uuid) -> Result<()> {
     Ok(())
 }
 
+/// Marks a job that was cut off by a shutdown as not yet executed, so that it
+/// is picked up again right away instead of waiting out the retry delay.
+pub async fn mark_job_interrupted(db: &DbClient, id: &Uuid) -> Result<()> {
+    tracing::trace!("mark_job_interrupted(id={})", id);
+
+    db.execute(
+        "UPDATE jobs SET executed_at = NULL, error_message = NULL WHERE id = $1",
+        &[&id],
+    )
+    .await
+    .context("Marking job as interrupted")?;
+
+    Ok(())
+}
+
 pub async fn get_job_by_name_and_scheduled_at(
     db: &DbClient,
     name: &str,
@@ -96,6 +133,29 @@ pub async fn get_job_by_name_and_scheduled_at(
     deserialize_job(&job)
 }
 
+/// Returns the jobs named `name` whose metadata contains `metadata`, in the
+/// order they are scheduled to run.
+///
+/// Jobs are deleted once they have run successfully, so these are all still
+/// pending.
+pub async fn get_jobs_by_name_and_metadata(
+    db: &DbClient,
+    name: &str,
+    metadata: &serde_json::Value,
+) -> Result<Vec<Job>> {
+    tracing::trace!("get_jobs_by_name_and_metadata(name={})", name);
+
+    let jobs = db
+        .query(
+            "SELECT * FROM jobs WHERE name = $1 AND metadata @> $2 ORDER BY scheduled_at",
+            &[&name, &metadata],
+        )
+        .await
+        .context("Select jobs by name and metadata")?;
+
+    jobs.iter().map(deserialize_job).collect()
+}
+
 // Selects all jobs with:
 //  - scheduled_at in the past
 //  - error_message is null or executed_at is at least 60 minutes ago (intended to make repeat executions rare enough)
@@ -1,3 +1,4 @@
+use crate::db::{audit::AuditEntry, ClientPool};
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -11,11 +12,13 @@ use once_cell::sync::OnceCell;
 use regex::Regex;
 use reqwest::header::{AUTHORIZATION, USER_AGENT};
 use reqwest::{Client, Request, RequestBuilder, Response, StatusCode};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::{
     fmt,
     time::{Duration, SystemTime},
 };
+use tokio::sync::mpsc;
 use tracing as log;
 
 pub type UserId = u64;
@@ -42,6 +45,7 @@ impl GithubClient {
                 resp = self.retry(req, sleep, MAX_ATTEMPTS).await?;
             }
         }
+        Self::record_rate_limit(&resp);
         let maybe_err = resp.error_for_status_ref().err();
         let body = resp
             .bytes()
@@ -75,6 +79,22 @@ impl GithubClient {
         Some(Duration::from_secs(Self::calc_sleep(reset_time) + 10))
     }
 
+    /// Exports the rate limit reported by a response to the metrics.
+    fn record_rate_limit(resp: &Response) {
+        let headers = resp.headers();
+        let remaining = headers
+            .get("X-RateLimit-Remaining")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        if let Some(remaining) = remaining {
+            let resource = headers
+                .get("X-RateLimit-Resource")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("core");
+            crate::metrics::record_github_rate_limit(resource, remaining);
+        }
+    }
+
     fn calc_sleep(reset_time: u64) -> u64 {
         let epoch_time = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_secs();
         reset_time.saturating_sub(epoch_time)
@@ -530,7 +550,11 @@ impl IssueRepository {
         format!("{}/{}", self.organization, self.repository)
     }
 
-    async fn has_label(&self, client: &GithubClient, label: &str) -> anyhow::Result<bool> {
+    pub(crate) async fn has_label(
+        &self,
+        client: &GithubClient,
+        label: &str,
+    ) -> anyhow::Result<bool> {
         #[allow(clippy::redundant_pattern_matching)]
         let url = format!("{}/labels/{}", self.url(client), label);
         match client.send_req(client.get(&url)).await {
@@ -546,6 +570,25 @@ impl IssueRepository {
             }
         }
     }
+
+    /// Returns all the labels defined in the repository.
+    pub(crate) async fn labels(&self, client: &GithubClient) -> anyhow::Result<Vec<Label>> {
+        let mut labels = Vec::new();
+        let mut page = 1;
+        loop {
+            let req = client.get(&format!(
+                "{}/labels?page={page}&per_page=100",
+                self.url(client)
+            ));
+            let new: Vec<Label> = client.json(req).await?;
+            if new.is_empty() {
+                break;
+            }
+            labels.extend(new);
+            page += 1;
+        }
+        Ok(labels)
+    }
 }
 
 #[derive(Debug)]
@@ -593,6 +636,24 @@ impl Issue {
         format!("{}#{}", self.repository(), self.number)
     }
 
+    /// The comment returned in place of a real one when the client is in
+    /// dry-run mode.
+    fn dry_run_comment(&self, id: u64, body: &str) -> Comment {
+        Comment {
+            id,
+            node_id: String::new(),
+            body: body.to_owned(),
+            html_url: self.html_url.clone(),
+            user: User {
+                login: String::new(),
+                id: 0,
+            },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            pr_review_state: None,
+        }
+    }
+
     pub fn is_pr(&self) -> bool {
         self.pull_request.is_some()
     }
@@ -622,6 +683,10 @@ impl Issue {
     }
 
     pub async fn edit_body(&self, client: &GithubClient, body: &str) -> anyhow::Result<()> {
+        let args = serde_json::json!({ "body": body });
+        if client.skip_mutation(self, "edit_body", &args) {
+            return Ok(());
+        }
         let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
         #[derive(serde::Serialize)]
         struct ChangedIssue<'a> {
@@ -631,6 +696,7 @@ impl Issue {
             .send_req(client.patch(&edit_url).json(&ChangedIssue { body }))
             .await
             .context("failed to edit issue body")?;
+        client.record_mutation(self, "edit_body", args);
         Ok(())
     }
 
@@ -640,6 +706,10 @@ impl Issue {
         id: u64,
         new_body: &str,
     ) -> anyhow::Result<Comment> {
+        let args = serde_json::json!({ "id": id, "body": new_body });
+        if client.skip_mutation(self, "edit_comment", &args) {
+            return Ok(self.dry_run_comment(id, new_body));
+        }
         let comment_url = format!("{}/issues/comments/{}", self.repository().url(client), id);
         #[derive(serde::Serialize)]
         struct NewComment<'a> {
@@ -653,10 +723,15 @@ impl Issue {
             )
             .await
             .context("failed to edit comment")?;
+        client.record_mutation(self, "edit_comment", args);
         Ok(comment)
     }
 
     pub async fn post_comment(&self, client: &GithubClient, body: &str) -> anyhow::Result<Comment> {
+        let args = serde_json::json!({ "body": body });
+        if client.skip_mutation(self, "post_comment", &args) {
+            return Ok(self.dry_run_comment(0, body));
+        }
         #[derive(serde::Serialize)]
         struct PostComment<'a> {
             body: &'a str,
@@ -670,15 +745,60 @@ impl Issue {
             .json(client.post(&comments_url).json(&PostComment { body }))
             .await
             .context("failed to post comment")?;
+        client.record_mutation(self, "post_comment", args);
         Ok(comment)
     }
 
+    /// Posts a review on this PR with just a comment, neither approving nor
+    /// requesting changes.
+    pub async fn post_review_comment(
+        &self,
+        client: &GithubClient,
+        body: &str,
+    ) -> anyhow::Result<()> {
+        let args = serde_json::json!({ "body": body });
+        if client.skip_mutation(self, "post_review_comment", &args) {
+            return Ok(());
+        }
+        let url = format!(
+            "{}/pulls/{}/reviews",
+            self.repository().url(client),
+            self.number
+        );
+        client
+            .send_req(client.post(&url).json(&serde_json::json!({
+                "body": body,
+                "event": "COMMENT",
+            })))
+            .await
+            .with_context(|| format!("failed to post review on {}", self.global_id()))?;
+        client.record_mutation(self, "post_review_comment", args);
+        Ok(())
+    }
+
+    /// Returns the first 100 reviews of this PR, oldest first.
+    pub async fn get_reviews(&self, client: &GithubClient) -> anyhow::Result<Vec<Comment>> {
+        let url = format!(
+            "{}/pulls/{}/reviews?per_page=100",
+            self.repository().url(client),
+            self.number
+        );
+        client
+            .json(client.get(&url))
+            .await
+            .with_context(|| format!("failed to get reviews of {}", self.global_id()))
+    }
+
     pub async fn hide_comment(
         &self,
         client: &GithubClient,
         node_id: &str,
         reason: ReportedContentClassifiers,
     ) -> anyhow::Result<()> {
+        let args = serde_json::json!({ "node_id": node_id, "reason": reason });
+        if client.skip_mutation(self, "hide_comment", &args) {
+            return Ok(());
+        }
         client
             .graphql_query(
                 "mutation($node_id: ID!, $reason: ReportedContentClassifiers!) {
@@ -692,6 +812,7 @@ impl Issue {
                 }),
             )
             .await?;
+        client.record_mutation(self, "hide_comment", args);
         Ok(())
     }
 
@@ -714,10 +835,16 @@ impl Issue {
             return Ok(());
         }
 
+        let args = serde_json::json!({ "label": label });
+        if client.skip_mutation(self, "remove_label", &args) {
+            return Ok(());
+        }
+
         client
             .send_req(client.delete(&url))
             .await
             .context("failed to delete label")?;
+        client.record_mutation(self, "remove_label", args);
 
         Ok(())
     }
@@ -766,6 +893,11 @@ impl Issue {
             .into());
         }
 
+        let args = serde_json::json!({ "labels": known_labels });
+        if client.skip_mutation(self, "add_labels", &args) {
+            return Ok(());
+        }
+
         #[derive(serde::Serialize)]
         struct LabelsReq {
             labels: Vec<String>,
@@ -777,6 +909,7 @@ impl Issue {
             }))
             .await
             .context("failed to add labels")?;
+        client.record_mutation(self, "add_labels", args);
 
         Ok(())
     }
@@ -818,6 +951,11 @@ impl Issue {
                 .collect::<Vec<_>>(),
         };
 
+        let args = serde_json::json!({ "assignees": assignees });
+        if client.skip_mutation(self, "remove_assignees", &args) {
+            return Ok(());
+        }
+
         #[derive(serde::Serialize)]
         struct AssigneeReq<'a> {
             assignees: &'a [&'a str],
@@ -828,6 +966,7 @@ impl Issue {
             }))
             .await
             .map_err(AssignmentError::Http)?;
+        client.record_mutation(self, "remove_assignees", args);
         Ok(())
     }
 
@@ -837,6 +976,10 @@ impl Issue {
         user: &str,
     ) -> Result<(), AssignmentError> {
         log::info!("add_assignee {} for {}", user, self.global_id());
+        let args = serde_json::json!({ "user": user });
+        if client.skip_mutation(self, "add_assignee", &args) {
+            return Ok(());
+        }
         let url = format!(
             "{repo_url}/issues/{number}/assignees",
             repo_url = self.repository().url(client),
@@ -860,6 +1003,7 @@ impl Issue {
             .any(|u| u.login.as_str().to_lowercase() == user.to_lowercase());
 
         if success {
+            client.record_mutation(self, "add_assignee", args);
             Ok(())
         } else {
             Err(AssignmentError::InvalidAssignee)
@@ -872,6 +1016,7 @@ impl Issue {
         user: &str,
     ) -> Result<(), AssignmentError> {
         log::info!("set_assignee for {} to {}", self.global_id(), user);
+        // Both calls are audited (or skipped in dry-run mode) by themselves.
         self.add_assignee(client, user).await?;
         self.remove_assignees(client, Selection::Except(user))
             .await?;
@@ -889,6 +1034,11 @@ impl Issue {
             title
         );
 
+        let args = serde_json::json!({ "title": title });
+        if client.skip_mutation(self, "set_milestone", &args) {
+            return Ok(());
+        }
+
         let full_repo_name = self.repository().full_repo_name();
         let milestone = client
             .get_or_create_milestone(&full_repo_name, title, "open")
@@ -897,6 +1047,46 @@ impl Issue {
         client
             .set_milestone(&full_repo_name, &milestone, self.number)
             .await?;
+        client.record_mutation(self, "set_milestone", args);
+        Ok(())
+    }
+
+    /// Sets the milestone of the issue or PR, if a milestone with that title
+    /// exists.
+    ///
+    /// Returns `false` if there is no such milestone.
+    pub async fn set_existing_milestone(
+        &self,
+        client: &GithubClient,
+        title: &str,
+    ) -> anyhow::Result<bool> {
+        let full_repo_name = self.repository().full_repo_name();
+        let Some(milestone) = client.find_milestone(&full_repo_name, title).await? else {
+            return Ok(false);
+        };
+
+        let args = serde_json::json!({ "title": title });
+        if client.skip_mutation(self, "set_milestone", &args) {
+            return Ok(true);
+        }
+
+        client
+            .set_milestone(&full_repo_name, &milestone, self.number)
+            .await?;
+        client.record_mutation(self, "set_milestone", args);
+        Ok(true)
+    }
+
+    /// Removes the milestone of the issue or PR.
+    pub async fn remove_milestone(&self, client: &GithubClient) -> anyhow::Result<()> {
+        let args = serde_json::Value::Null;
+        if client.skip_mutation(self, "remove_milestone", &args) {
+            return Ok(());
+        }
+        client
+            .remove_milestone(&self.repository().full_repo_name(), self.number)
+            .await?;
+        client.record_mutation(self, "remove_milestone", args);
         Ok(())
     }
 
@@ -906,6 +1096,10 @@ impl Issue {
         client: &GithubClient,
         reason: Option<LockReason>,
     ) -> anyhow::Result<()> {
+        let args = serde_json::json!({ "reason": reason });
+        if client.skip_mutation(self, "lock", &args) {
+            return Ok(());
+        }
         let lock_url = format!(
             "{}/issues/{}/lock",
             self.repository().url(client),
@@ -927,10 +1121,34 @@ impl Issue {
             })
             .await
             .context("failed to lock issue")?;
+        client.record_mutation(self, "lock", args);
+        Ok(())
+    }
+
+    /// Unlock a locked issue.
+    pub async fn unlock(&self, client: &GithubClient) -> anyhow::Result<()> {
+        let args = serde_json::Value::Null;
+        if client.skip_mutation(self, "unlock", &args) {
+            return Ok(());
+        }
+        let lock_url = format!(
+            "{}/issues/{}/lock",
+            self.repository().url(client),
+            self.number
+        );
+        client
+            .send_req(client.delete(&lock_url))
+            .await
+            .context("failed to unlock issue")?;
+        client.record_mutation(self, "unlock", args);
         Ok(())
     }
 
     pub async fn close(&self, client: &GithubClient) -> anyhow::Result<()> {
+        let args = serde_json::Value::Null;
+        if client.skip_mutation(self, "close", &args) {
+            return Ok(());
+        }
         let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
         #[derive(serde::Serialize)]
         struct CloseIssue<'a> {
@@ -944,6 +1162,25 @@ impl Issue {
             )
             .await
             .context("failed to close issue")?;
+        client.record_mutation(self, "close", args);
+        Ok(())
+    }
+
+    pub async fn reopen(&self, client: &GithubClient) -> anyhow::Result<()> {
+        let args = serde_json::Value::Null;
+        if client.skip_mutation(self, "reopen", &args) {
+            return Ok(());
+        }
+        let edit_url = format!("{}/issues/{}", self.repository().url(client), self.number);
+        #[derive(serde::Serialize)]
+        struct ReopenIssue<'a> {
+            state: &'a str,
+        }
+        client
+            .send_req(client.patch(&edit_url).json(&ReopenIssue { state: "open" }))
+            .await
+            .context("failed to reopen issue")?;
+        client.record_mutation(self, "reopen", args);
         Ok(())
     }
 
@@ -1057,6 +1294,10 @@ impl Issue {
     ) -> anyhow::Result<()> {
         let issue_id = self.graphql_issue_id(client).await?;
         let repo_id = client.graphql_repo_id(owner, repo).await?;
+        let args = serde_json::json!({ "owner": owner, "repo": repo });
+        if client.skip_mutation(self, "transfer", &args) {
+            return Ok(());
+        }
         client
             .graphql_query(
                 "mutation ($issueId: ID!, $repoId: ID!) {
@@ -1074,6 +1315,7 @@ impl Issue {
                 }),
             )
             .await?;
+        client.record_mutation(self, "transfer", args);
         Ok(())
     }
 }
@@ -1122,16 +1364,49 @@ pub struct PullRequestReviewEvent {
     pub repository: Repository,
 }
 
+/// A pull request review comment event.
+///
+/// <https://docs.github.com/en/developers/webhooks-and-events/webhooks/webhook-events-and-payloads#pull_request_review_comment>
 #[derive(Debug, serde::Deserialize)]
 pub struct PullRequestReviewComment {
     pub action: IssueCommentAction,
     pub changes: Option<Changes>,
     #[serde(rename = "pull_request")]
     pub issue: Issue,
-    pub comment: Comment,
+    pub comment: ReviewComment,
     pub repository: Repository,
 }
 
+/// A comment on the diff of a pull request.
+#[derive(Debug, serde::Deserialize)]
+pub struct ReviewComment {
+    #[serde(flatten)]
+    pub comment: Comment,
+    /// The file the comment is on.
+    pub path: String,
+    /// The line the comment is on, or the last line of a multi-line comment.
+    ///
+    /// This is `None` if the line is no longer part of the diff.
+    pub line: Option<u32>,
+    /// The first line of a multi-line comment.
+    pub start_line: Option<u32>,
+    /// Which version of the file `line` is in.
+    pub side: Option<DiffSide>,
+    /// The commit the comment was made on.
+    pub commit_id: String,
+    /// The comment this one replies to in a review thread.
+    pub in_reply_to_id: Option<u64>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DiffSide {
+    /// The old version of the file, for removed lines.
+    Left,
+    /// The new version of the file, for added and unchanged lines.
+    Right,
+}
+
 #[derive(PartialEq, Eq, Debug, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum IssueCommentAction {
@@ -1896,6 +2171,73 @@ impl Repository {
             .await
     }
 
+    /// Returns the paths of all files in the given commit, or `None` if the
+    /// repository is too large for GitHub to list them in one response.
+    pub async fn get_file_paths(
+        &self,
+        client: &GithubClient,
+        sha: &str,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        #[derive(serde::Deserialize)]
+        struct Tree {
+            tree: Vec<TreeEntry>,
+            truncated: bool,
+        }
+        #[derive(serde::Deserialize)]
+        struct TreeEntry {
+            path: String,
+            #[serde(rename = "type")]
+            object_type: String,
+        }
+
+        let url = format!("{}/git/trees/{sha}?recursive=1", self.url(client));
+        let tree: Tree = client
+            .json(client.get(&url))
+            .await
+            .with_context(|| format!("failed to get tree of {sha} in {}", self.full_name))?;
+        if tree.truncated {
+            return Ok(None);
+        }
+        Ok(Some(
+            tree.tree
+                .into_iter()
+                .filter(|entry| entry.object_type == "blob")
+                .map(|entry| entry.path)
+                .collect(),
+        ))
+    }
+
+    /// Returns the check runs for the given commit.
+    pub async fn get_check_runs(
+        &self,
+        client: &GithubClient,
+        sha: &str,
+    ) -> anyhow::Result<Vec<CheckRun>> {
+        #[derive(serde::Deserialize)]
+        struct CheckRuns {
+            check_runs: Vec<CheckRun>,
+        }
+
+        let mut check_runs = Vec::new();
+        let mut page = 1;
+        loop {
+            let url = format!(
+                "{}/commits/{sha}/check-runs?page={page}&per_page=100",
+                self.url(client)
+            );
+            let response: CheckRuns = client
+                .json(client.get(&url))
+                .await
+                .with_context(|| format!("failed to get check runs for {sha}"))?;
+            if response.check_runs.is_empty() {
+                break;
+            }
+            check_runs.extend(response.check_runs);
+            page += 1;
+        }
+        Ok(check_runs)
+    }
+
     pub async fn get_issue(&self, client: &GithubClient, issue_num: u64) -> anyhow::Result<Issue> {
         let url = format!("{}/pulls/{issue_num}", self.url(client));
         client
@@ -2262,6 +2604,162 @@ pub struct PushEvent {
     pub git_ref: String,
     pub repository: Repository,
     sender: User,
+    /// The pushed commits. GitHub caps this list, so very large pushes may
+    /// not list every commit.
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+}
+
+impl PushEvent {
+    /// Whether this is a push to the repository's default branch.
+    pub fn is_to_default_branch(&self) -> bool {
+        self.git_ref == format!("refs/heads/{}", self.repository.default_branch)
+    }
+
+    /// Whether any of the pushed commits added, removed or modified `path`.
+    pub fn touches(&self, path: &str) -> bool {
+        self.commits.iter().any(|commit| {
+            commit
+                .added
+                .iter()
+                .chain(&commit.removed)
+                .chain(&commit.modified)
+                .any(|p| p == path)
+        })
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PushCommit {
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub modified: Vec<String>,
+}
+
+/// The conclusion of a completed check run, check suite or workflow run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckConclusion {
+    Success,
+    Failure,
+    Neutral,
+    Cancelled,
+    Skipped,
+    TimedOut,
+    ActionRequired,
+    Stale,
+    StartupFailure,
+    /// A conclusion GitHub added after this was written.
+    #[serde(other)]
+    Unknown,
+}
+
+impl CheckConclusion {
+    /// Whether this conclusion means the check did not pass.
+    pub fn is_failure(self) -> bool {
+        matches!(
+            self,
+            CheckConclusion::Failure
+                | CheckConclusion::TimedOut
+                | CheckConclusion::ActionRequired
+                | CheckConclusion::StartupFailure
+        )
+    }
+}
+
+/// A PR a check run, check suite or workflow run was triggered for.
+///
+/// GitHub only lists PRs whose head branch lives in the same repository, so
+/// this is empty for PRs from forks.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AssociatedPullRequest {
+    pub number: u64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckRun {
+    pub id: u64,
+    pub name: String,
+    pub head_sha: String,
+    /// `None` until the check run has completed.
+    pub conclusion: Option<CheckConclusion>,
+    pub html_url: Option<String>,
+    #[serde(default)]
+    pub pull_requests: Vec<AssociatedPullRequest>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckSuite {
+    pub id: u64,
+    pub head_sha: String,
+    /// `None` until the check suite has completed.
+    pub conclusion: Option<CheckConclusion>,
+    #[serde(default)]
+    pub pull_requests: Vec<AssociatedPullRequest>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WorkflowRun {
+    pub id: u64,
+    pub name: Option<String>,
+    pub head_sha: String,
+    /// `None` until the workflow run has completed.
+    pub conclusion: Option<CheckConclusion>,
+    pub html_url: String,
+    #[serde(default)]
+    pub pull_requests: Vec<AssociatedPullRequest>,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunAction {
+    Created,
+    Completed,
+    Rerequested,
+    RequestedAction,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckSuiteAction {
+    Completed,
+    Requested,
+    Rerequested,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowRunAction {
+    Requested,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckRunEvent {
+    pub action: CheckRunAction,
+    pub check_run: CheckRun,
+    pub repository: Repository,
+    sender: User,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckSuiteEvent {
+    pub action: CheckSuiteAction,
+    pub check_suite: CheckSuite,
+    pub repository: Repository,
+    sender: User,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WorkflowRunEvent {
+    pub action: WorkflowRunAction,
+    pub workflow_run: WorkflowRun,
+    pub repository: Repository,
+    sender: User,
 }
 
 /// An event triggered by a webhook.
@@ -2270,19 +2768,21 @@ pub enum Event {
     /// A Git branch or tag is created.
     Create(CreateEvent),
     /// A comment on an issue or PR.
-    ///
-    /// Can be:
-    /// - Regular comment on an issue or PR.
-    /// - A PR review.
-    /// - A comment on a PR review.
-    ///
-    /// These different scenarios are unified into the `IssueComment` variant
-    /// when triagebot receives the corresponding webhook event.
     IssueComment(IssueCommentEvent),
+    /// A review of a PR is submitted, edited or dismissed.
+    PullRequestReview(PullRequestReviewEvent),
+    /// A comment on the diff of a PR, usually part of a review.
+    PullRequestReviewComment(PullRequestReviewComment),
     /// Activity on an issue or PR.
     Issue(IssuesEvent),
     /// One or more commits are pushed to a repository branch or tag.
     Push(PushEvent),
+    /// Activity on a single check.
+    CheckRun(CheckRunEvent),
+    /// Activity on the set of checks an app ran for a commit.
+    CheckSuite(CheckSuiteEvent),
+    /// Activity on a GitHub Actions workflow run.
+    WorkflowRun(WorkflowRunEvent),
 }
 
 impl Event {
@@ -2290,17 +2790,26 @@ impl Event {
         match self {
             Event::Create(event) => &event.repository,
             Event::IssueComment(event) => &event.repository,
+            Event::PullRequestReview(event) => &event.repository,
+            Event::PullRequestReviewComment(event) => &event.repository,
             Event::Issue(event) => &event.repository,
             Event::Push(event) => &event.repository,
+            Event::CheckRun(event) => &event.repository,
+            Event::CheckSuite(event) => &event.repository,
+            Event::WorkflowRun(event) => &event.repository,
         }
     }
 
+    /// The issue or PR this event is about.
     pub fn issue(&self) -> Option<&Issue> {
         match self {
             Event::Create(_) => None,
             Event::IssueComment(event) => Some(&event.issue),
+            Event::PullRequestReview(event) => Some(&event.pull_request),
+            Event::PullRequestReviewComment(event) => Some(&event.issue),
             Event::Issue(event) => Some(&event.issue),
             Event::Push(_) => None,
+            Event::CheckRun(_) | Event::CheckSuite(_) | Event::WorkflowRun(_) => None,
         }
     }
 
@@ -2310,7 +2819,10 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.issue.body),
             Event::IssueComment(e) => Some(&e.comment.body),
+            Event::PullRequestReview(e) => Some(&e.review.body),
+            Event::PullRequestReviewComment(e) => Some(&e.comment.comment.body),
             Event::Push(_) => None,
+            Event::CheckRun(_) | Event::CheckSuite(_) | Event::WorkflowRun(_) => None,
         }
     }
 
@@ -2320,7 +2832,10 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
             Event::IssueComment(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
+            Event::PullRequestReview(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
+            Event::PullRequestReviewComment(e) => Some(&e.changes.as_ref()?.body.as_ref()?.from),
             Event::Push(_) => None,
+            Event::CheckRun(_) | Event::CheckSuite(_) | Event::WorkflowRun(_) => None,
         }
     }
 
@@ -2329,7 +2844,12 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(&e.issue.html_url),
             Event::IssueComment(e) => Some(&e.comment.html_url),
+            Event::PullRequestReview(e) => Some(&e.review.html_url),
+            Event::PullRequestReviewComment(e) => Some(&e.comment.comment.html_url),
             Event::Push(_) => None,
+            Event::CheckRun(e) => e.check_run.html_url.as_deref(),
+            Event::CheckSuite(_) => None,
+            Event::WorkflowRun(e) => Some(&e.workflow_run.html_url),
         }
     }
 
@@ -2338,7 +2858,12 @@ impl Event {
             Event::Create(e) => &e.sender,
             Event::Issue(e) => &e.issue.user,
             Event::IssueComment(e) => &e.comment.user,
+            Event::PullRequestReview(e) => &e.review.user,
+            Event::PullRequestReviewComment(e) => &e.comment.comment.user,
             Event::Push(e) => &e.sender,
+            Event::CheckRun(e) => &e.sender,
+            Event::CheckSuite(e) => &e.sender,
+            Event::WorkflowRun(e) => &e.sender,
         }
     }
 
@@ -2347,7 +2872,10 @@ impl Event {
             Event::Create(_) => None,
             Event::Issue(e) => Some(e.issue.created_at.into()),
             Event::IssueComment(e) => Some(e.comment.updated_at.into()),
+            Event::PullRequestReview(e) => Some(e.review.updated_at.into()),
+            Event::PullRequestReviewComment(e) => Some(e.comment.comment.updated_at.into()),
             Event::Push(_) => None,
+            Event::CheckRun(_) | Event::CheckSuite(_) | Event::WorkflowRun(_) => None,
         }
     }
 }
@@ -2409,6 +2937,25 @@ pub struct GithubClient {
     raw_url: String,
     /// If `true`, requests will sleep if it hits GitHub's rate limit.
     retry_rate_limit: bool,
+    /// If set, mutations are recorded here instead of being sent to GitHub.
+    dry_run: Option<Arc<Mutex<VecDeque<DryRunAction>>>>,
+    /// If set, successful mutations are sent here to be recorded in the
+    /// `audit_log` table, see [`crate::audit::spawn_writer`].
+    audit_log: Option<mpsc::UnboundedSender<AuditEntry>>,
+}
+
+/// How many dry-run actions are kept in memory.
+const DRY_RUN_LOG_LIMIT: usize = 1000;
+
+/// A mutation that was skipped because the client is in dry-run mode.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DryRunAction {
+    pub time: DateTime<Utc>,
+    /// The issue or PR the action applies to, e.g. `rust-lang/rust#123`.
+    pub issue: String,
+    /// The name of the `Issue` method that was called.
+    pub kind: &'static str,
+    pub args: serde_json::Value,
 }
 
 impl GithubClient {
@@ -2420,6 +2967,8 @@ impl GithubClient {
             graphql_url,
             raw_url,
             retry_rate_limit: false,
+            dry_run: None,
+            audit_log: None,
         }
     }
 
@@ -2435,6 +2984,15 @@ impl GithubClient {
         )
     }
 
+    /// Returns a client that authenticates with `token` instead, sharing the
+    /// rest of this client's settings (including the dry-run log).
+    pub fn with_token(&self, token: String) -> GithubClient {
+        GithubClient {
+            token,
+            ..self.clone()
+        }
+    }
+
     /// Sets whether or not this client will retry when it hits GitHub's rate limit.
     ///
     /// Just beware that the retry may take a long time (like 30 minutes,
@@ -2443,6 +3001,83 @@ impl GithubClient {
         self.retry_rate_limit = retry;
     }
 
+    /// Sets whether or not this client is in dry-run mode.
+    ///
+    /// In dry-run mode, mutating `Issue` methods (posting comments, changing
+    /// labels, assigning, closing, ...) record a [`DryRunAction`] instead of
+    /// calling the API. Reads still go to GitHub, so handlers make the same
+    /// decisions they would otherwise.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run.then(Default::default);
+    }
+
+    /// Whether a GitHub user with the given login exists.
+    pub async fn user_exists(&self, login: &str) -> anyhow::Result<bool> {
+        let url = format!("{}/users/{login}", self.api_url);
+        match self.send_req(self.get(&url)).await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.downcast_ref::<reqwest::Error>()
+                    .map_or(false, |e| e.status() == Some(StatusCode::NOT_FOUND))
+                {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Records every mutation made through this client in the audit log, see
+    /// [`crate::audit`].
+    pub fn set_audit_log(&mut self, db: ClientPool) {
+        self.audit_log = Some(crate::audit::spawn_writer(db));
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// Returns the most recent actions skipped in dry-run mode, oldest first.
+    pub fn dry_run_actions(&self) -> Vec<DryRunAction> {
+        match &self.dry_run {
+            Some(actions) => actions.lock().unwrap().iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Called by mutating `Issue` methods before they send their request.
+    ///
+    /// In dry-run mode this records `kind` in the dry-run log and returns
+    /// `true`, meaning the caller must skip the mutation.
+    fn skip_mutation(&self, issue: &Issue, kind: &'static str, args: &serde_json::Value) -> bool {
+        let Some(actions) = &self.dry_run else {
+            return false;
+        };
+        log::info!("dry-run: {kind} on {} {args}", issue.global_id());
+        let mut actions = actions.lock().unwrap();
+        if actions.len() == DRY_RUN_LOG_LIMIT {
+            actions.pop_front();
+        }
+        actions.push_back(DryRunAction {
+            time: Utc::now(),
+            issue: issue.global_id(),
+            kind,
+            args: args.clone(),
+        });
+        true
+    }
+
+    /// Called by mutating `Issue` methods once their request succeeded, to
+    /// record it in the audit log, if enabled.
+    fn record_mutation(&self, issue: &Issue, kind: &str, args: serde_json::Value) {
+        if let Some(audit_log) = &self.audit_log {
+            // The writer only stops with the runtime, there is no one left to
+            // tell then.
+            let _ = audit_log.send(crate::audit::entry(issue, kind, args));
+        }
+    }
+
     pub fn raw(&self) -> &Client {
         &self.client
     }
@@ -2472,6 +3107,34 @@ impl GithubClient {
         }
     }
 
+    /// Returns the contents of `path` on the default branch of `repo`, or `None`
+    /// if there is no such file.
+    ///
+    /// Unlike [`Self::raw_file`], this goes through the API, whose responses
+    /// aren't cached by a CDN, so a file changed by a push is seen right away.
+    pub async fn file_contents(&self, repo: &str, path: &str) -> anyhow::Result<Option<Bytes>> {
+        let url = format!("{}/repos/{repo}/contents/{path}", self.api_url);
+        let req = self
+            .get(&url)
+            .header(reqwest::header::ACCEPT, "application/vnd.github.raw");
+        let req_dbg = format!("{:?}", req);
+        let req = req
+            .build()
+            .with_context(|| format!("failed to build request {:?}", req_dbg))?;
+        let resp = self.client.execute(req).await.context(req_dbg.clone())?;
+        Self::record_rate_limit(&resp);
+        let status = resp.status();
+        let body = resp
+            .bytes()
+            .await
+            .with_context(|| format!("failed to read response body {req_dbg}"))?;
+        match status {
+            StatusCode::OK => Ok(Some(body)),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => anyhow::bail!("failed to GET {}: {}", url, status),
+        }
+    }
+
     /// Get the raw gist content from the URL of the HTML version of the gist:
     ///
     /// `html_url` looks like `https://gist.github.com/rust-play/7e80ca3b1ec7abe08f60c41aff91f060`.
@@ -2729,6 +3392,18 @@ impl GithubClient {
             }
         }
         // In the case where it already exists, we need to search for its number.
+        match self.find_milestone(full_repo_name, title).await? {
+            Some(milestone) => Ok(milestone),
+            None => anyhow::bail!("expected to find milestone with title {title}"),
+        }
+    }
+
+    /// Finds the open or closed [`Milestone`] with the given title.
+    async fn find_milestone(
+        &self,
+        full_repo_name: &str,
+        title: &str,
+    ) -> anyhow::Result<Option<Milestone>> {
         let mut page = 1;
         loop {
             let url = format!(
@@ -2740,15 +3415,23 @@ impl GithubClient {
                 .await
                 .with_context(|| format!("failed to get milestones {url} searching for {title}"))?;
             if milestones.is_empty() {
-                anyhow::bail!("expected to find milestone with title {title}");
+                return Ok(None);
             }
             if let Some(milestone) = milestones.into_iter().find(|m| m.title == title) {
-                return Ok(milestone);
+                return Ok(Some(milestone));
             }
             page += 1;
         }
     }
 
+    /// Fetches an issue or PR.
+    pub async fn get_issue(&self, full_repo_name: &str, issue_num: u64) -> anyhow::Result<Issue> {
+        let url = format!("{}/repos/{full_repo_name}/issues/{issue_num}", self.api_url);
+        self.json(self.get(&url))
+            .await
+            .with_context(|| format!("failed to get issue {full_repo_name}#{issue_num}"))
+    }
+
     /// Set the milestone of an issue or PR.
     pub async fn set_milestone(
         &self,
@@ -2765,6 +3448,17 @@ impl GithubClient {
         Ok(())
     }
 
+    /// Remove the milestone of an issue or PR.
+    async fn remove_milestone(&self, full_repo_name: &str, issue_num: u64) -> anyhow::Result<()> {
+        let url = format!("{}/repos/{full_repo_name}/issues/{issue_num}", self.api_url);
+        self.send_req(self.patch(&url).json(&serde_json::json!({
+            "milestone": null
+        })))
+        .await
+        .with_context(|| format!("failed to remove milestone for {url}"))?;
+        Ok(())
+    }
+
     /// Returns the GraphQL ID of the given repository.
     async fn graphql_repo_id(&self, owner: &str, repo: &str) -> anyhow::Result<String> {
         let mut repo_id = self
@@ -3210,6 +3904,68 @@ impl Submodule {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn dry_run_records_mutations() {
+        let mut client = GithubClient::new(
+            "fake-token".to_string(),
+            "https://api.github.invalid".to_string(),
+            "https://api.github.invalid/graphql".to_string(),
+            "https://raw.github.invalid".to_string(),
+        );
+        client.set_dry_run(true);
+        let issue = crate::tests::github::issue().number(5).call();
+
+        let comment = issue.post_comment(&client, "hello").await.unwrap();
+        assert_eq!(comment.body, "hello");
+        issue.set_assignee(&client, "octocat").await.unwrap();
+        issue.close(&client).await.unwrap();
+
+        let actions = client.dry_run_actions();
+        let kinds: Vec<_> = actions.iter().map(|a| a.kind).collect();
+        // Setting the assignee adds them and removes everyone else.
+        assert_eq!(
+            kinds,
+            ["post_comment", "add_assignee", "remove_assignees", "close"]
+        );
+        assert_eq!(actions[0].args["body"], "hello");
+        assert_eq!(actions[1].args["user"], "octocat");
+    }
+
+    #[test]
+    fn deserialize_review_comment() {
+        let comment: ReviewComment = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "node_id": "PRRC_1",
+            "body": "@rustbot label +T-compiler",
+            "html_url": "https://github.com/rust-lang/rust/pull/1#discussion_r1",
+            "user": { "login": "octocat", "id": 2 },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+            "path": "compiler/rustc_ast/src/lib.rs",
+            "line": 12,
+            "start_line": null,
+            "side": "RIGHT",
+            "commit_id": "abc123",
+            "diff_hunk": "@@ -1,3 +1,4 @@",
+        }))
+        .unwrap();
+        assert_eq!(comment.comment.body, "@rustbot label +T-compiler");
+        assert_eq!(comment.comment.user.login, "octocat");
+        assert_eq!(comment.path, "compiler/rustc_ast/src/lib.rs");
+        assert_eq!(comment.line, Some(12));
+        assert_eq!(comment.side, Some(DiffSide::Right));
+        assert_eq!(comment.in_reply_to_id, None);
+    }
+
+    #[test]
+    fn deserialize_unknown_conclusion() {
+        let conclusion: CheckConclusion = serde_json::from_value("timed_out".into()).unwrap();
+        assert_eq!(conclusion, CheckConclusion::TimedOut);
+        let conclusion: CheckConclusion = serde_json::from_value("sunny".into()).unwrap();
+        assert_eq!(conclusion, CheckConclusion::Unknown);
+        assert!(!conclusion.is_failure());
+    }
+
     #[test]
     fn display_labels() {
         let x = UnknownLabels {
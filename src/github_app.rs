@@ -0,0 +1,204 @@
+//! Authentication as a GitHub App.
+//!
+//! When `GITHUB_APP_ID` and `GITHUB_APP_PRIVATE_KEY` are set, webhook events
+//! from repositories where the app is installed are handled with a token for
+//! that installation instead of the personal access token in `GITHUB_TOKEN`.
+//! This lets other organizations install the bot, and installation tokens get
+//! their own rate limit.
+//!
+//! Installation tokens are minted by authenticating as the app with a JWT
+//! signed by its private key, and are cached until shortly before they expire.
+
+use anyhow::Context as _;
+use chrono::{DateTime, Duration, Utc};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use reqwest::header::{ACCEPT, AUTHORIZATION, USER_AGENT};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing as log;
+
+/// Cached tokens are refreshed when they have less than this long left.
+const TOKEN_REFRESH_MARGIN_MINS: i64 = 5;
+
+pub struct GithubApp {
+    app_id: u64,
+    key: PKey<Private>,
+    api_url: String,
+    client: reqwest::Client,
+    /// Installation id -> token.
+    tokens: Mutex<HashMap<u64, InstallationToken>>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl GithubApp {
+    pub fn new(app_id: u64, private_key_pem: &[u8], api_url: String) -> anyhow::Result<GithubApp> {
+        let key = PKey::private_key_from_pem(private_key_pem)
+            .context("failed to parse the GitHub App private key")?;
+        Ok(GithubApp {
+            app_id,
+            key,
+            api_url,
+            client: reqwest::Client::new(),
+            tokens: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the app configured in the environment, if any.
+    pub fn from_env() -> anyhow::Result<Option<GithubApp>> {
+        let Ok(app_id) = std::env::var("GITHUB_APP_ID") else {
+            return Ok(None);
+        };
+        let app_id = app_id
+            .parse()
+            .with_context(|| format!("GITHUB_APP_ID `{app_id}` is not a number"))?;
+        let key = std::env::var("GITHUB_APP_PRIVATE_KEY")
+            .context("GITHUB_APP_ID is set but GITHUB_APP_PRIVATE_KEY is not")?;
+        // Allow the PEM to be given on a single line in `.env` files.
+        let key = key.replace("\\n", "\n");
+        let api_url = std::env::var("GITHUB_API_URL")
+            .unwrap_or_else(|_| "https://api.github.com".to_string());
+        GithubApp::new(app_id, key.as_bytes(), api_url).map(Some)
+    }
+
+    /// Returns a token for acting as the given installation of the app.
+    pub async fn installation_token(&self, installation_id: u64) -> anyhow::Result<String> {
+        let refresh_after = Utc::now() + Duration::minutes(TOKEN_REFRESH_MARGIN_MINS);
+        if let Some(token) = self.tokens.lock().unwrap().get(&installation_id) {
+            if token.expires_at > refresh_after {
+                return Ok(token.token.clone());
+            }
+        }
+
+        log::info!("minting a token for GitHub App installation {installation_id}");
+        let url = format!(
+            "{}/app/installations/{installation_id}/access_tokens",
+            self.api_url
+        );
+        let token: InstallationToken = self
+            .client
+            .post(&url)
+            .header(AUTHORIZATION, format!("Bearer {}", self.jwt()?))
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "rust-lang-triagebot")
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to get a token for installation {installation_id}"))?
+            .json()
+            .await
+            .context("failed to parse installation token")?;
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(installation_id, token.clone());
+        Ok(token.token)
+    }
+
+    /// Creates a JWT authenticating as the app itself, valid for a few
+    /// minutes.
+    fn jwt(&self) -> anyhow::Result<String> {
+        let now = Utc::now().timestamp();
+        let header = serde_json::json!({ "alg": "RS256", "typ": "JWT" });
+        let claims = serde_json::json!({
+            // Backdated to allow for clock drift, as GitHub recommends.
+            "iat": now - 60,
+            "exp": now + 9 * 60,
+            "iss": self.app_id.to_string(),
+        });
+        let message = format!(
+            "{}.{}",
+            base64_url(header.to_string().as_bytes()),
+            base64_url(claims.to_string().as_bytes())
+        );
+        let mut signer = Signer::new(MessageDigest::sha256(), &self.key)?;
+        signer.update(message.as_bytes())?;
+        let signature = signer.sign_to_vec()?;
+        Ok(format!("{message}.{}", base64_url(&signature)))
+    }
+}
+
+/// Base64url encoding without padding, as used by JWTs.
+fn base64_url(data: &[u8]) -> String {
+    openssl::base64::encode_block(data)
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+/// Returns the id of the app installation that sent a webhook payload.
+pub fn installation_id(payload: &str) -> Option<u64> {
+    #[derive(serde::Deserialize)]
+    struct Installation {
+        id: u64,
+    }
+    #[derive(serde::Deserialize)]
+    struct Payload {
+        installation: Option<Installation>,
+    }
+    let payload: Payload = serde_json::from_str(payload).ok()?;
+    Some(payload.installation?.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::rsa::Rsa;
+    use openssl::sign::Verifier;
+
+    fn base64_url_decode(s: &str) -> Vec<u8> {
+        let mut s = s.replace('-', "+").replace('_', "/");
+        while s.len() % 4 != 0 {
+            s.push('=');
+        }
+        openssl::base64::decode_block(&s).unwrap()
+    }
+
+    #[test]
+    fn jwt_is_signed_by_app_key() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let pem = key.private_key_to_pem_pkcs8().unwrap();
+        let app = GithubApp::new(1234, &pem, "https://api.github.com".to_string()).unwrap();
+
+        let jwt = app.jwt().unwrap();
+        let parts: Vec<_> = jwt.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(!jwt.contains('='));
+
+        let header: serde_json::Value =
+            serde_json::from_slice(&base64_url_decode(parts[0])).unwrap();
+        assert_eq!(header["alg"], "RS256");
+        let claims: serde_json::Value =
+            serde_json::from_slice(&base64_url_decode(parts[1])).unwrap();
+        assert_eq!(claims["iss"], "1234");
+        assert!(claims["exp"].as_i64().unwrap() > Utc::now().timestamp());
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        verifier
+            .update(format!("{}.{}", parts[0], parts[1]).as_bytes())
+            .unwrap();
+        assert!(verifier.verify(&base64_url_decode(parts[2])).unwrap());
+    }
+
+    #[test]
+    fn base64_url_alphabet() {
+        assert_eq!(base64_url(&[0xfb, 0xff]), "-_8");
+        assert_eq!(base64_url(b"ab"), "YWI");
+    }
+
+    #[test]
+    fn installation_id_from_payload() {
+        assert_eq!(
+            installation_id(r#"{"action": "opened", "installation": {"id": 42, "node_id": "x"}}"#),
+            Some(42)
+        );
+        assert_eq!(installation_id(r#"{"action": "opened"}"#), None);
+        assert_eq!(installation_id("not json"), None);
+    }
+}
@@ -1,9 +1,15 @@
 use crate::config::{self, Config, ConfigurationError};
-use crate::github::{Event, GithubClient, IssueCommentAction, IssuesAction, IssuesEvent};
+use crate::github::{
+    Event, GithubClient, IssueCommentAction, IssueCommentEvent, IssuesAction, IssuesEvent,
+    PullRequestReviewAction, PullRequestReviewComment,
+};
+use crate::github_app::GithubApp;
 use crate::handlers::pr_tracking::ReviewerWorkqueue;
+use crate::metrics;
+use anyhow::Context as _;
 use octocrab::Octocrab;
 use parser::command::{assign::AssignCommand, Command, Input};
-use std::fmt;
+use std::fmt::{self, Write as _};
 use std::sync::Arc;
 use tracing as log;
 
@@ -15,6 +21,15 @@ pub enum HandlerError {
 
 impl std::error::Error for HandlerError {}
 
+impl From<anyhow::Error> for HandlerError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast::<UserError>() {
+            Ok(UserError(msg)) => HandlerError::Message(msg),
+            Err(err) => HandlerError::Other(err),
+        }
+    }
+}
+
 impl fmt::Display for HandlerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -24,18 +39,35 @@ impl fmt::Display for HandlerError {
     }
 }
 
+/// An error in what the user asked for, which a handler returns to have it
+/// reported in a comment on the issue rather than logged.
+#[derive(Debug)]
+pub(crate) struct UserError(pub(crate) String);
+
+impl std::error::Error for UserError {}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 mod assign;
 mod autolabel;
 mod bot_pull_requests;
 mod canonicalize_issue_links;
 mod check_commits;
+mod ci_failures;
 mod close;
 pub mod docs_update;
 mod github_releases;
 mod glacier;
+mod help;
+mod lock;
 mod major_change;
 mod mentions;
 mod merge_conflicts;
+mod milestone;
 mod milestone_prs;
 mod no_merges;
 mod nominate;
@@ -49,7 +81,9 @@ pub mod project_goals;
 pub mod pull_requests_assignment_update;
 mod relabel;
 mod relnotes;
+pub mod remind;
 mod rendered_link;
+mod reopen;
 mod review_requested;
 mod review_submitted;
 pub mod rustc_commits;
@@ -59,6 +93,12 @@ pub mod types_planning_updates;
 mod validate_config;
 
 pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
+    if let Event::Push(push) = event {
+        if push.is_to_default_branch() {
+            config::invalidate(&push.repository.full_name, |path| push.touches(path));
+        }
+    }
+
     let config = config::get(&ctx.github, event.repo()).await;
     if let Err(e) = &config {
         log::warn!("configuration error {}: {e}", event.repo().full_name);
@@ -74,7 +114,10 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
     }
 
     if let Ok(config) = &config {
-        if let Err(e) = check_commits::handle(ctx, event, &config).await {
+        if let Err(e) =
+            metrics::track_handler("check_commits", check_commits::handle(ctx, event, &config))
+                .await
+        {
             log::error!(
                 "failed to process event {:?} with `check_commits` handler: {:?}",
                 event,
@@ -83,7 +126,8 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         }
     }
 
-    if let Err(e) = project_goals::handle(ctx, event).await {
+    if let Err(e) = metrics::track_handler("project_goals", project_goals::handle(ctx, event)).await
+    {
         log::error!(
             "failed to process event {:?} with `project_goals` handler: {:?}",
             event,
@@ -91,7 +135,7 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         );
     }
 
-    if let Err(e) = notification::handle(ctx, event).await {
+    if let Err(e) = metrics::track_handler("notification", notification::handle(ctx, event)).await {
         log::error!(
             "failed to process event {:?} with notification handler: {:?}",
             event,
@@ -99,7 +143,8 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         );
     }
 
-    if let Err(e) = rustc_commits::handle(ctx, event).await {
+    if let Err(e) = metrics::track_handler("rustc_commits", rustc_commits::handle(ctx, event)).await
+    {
         log::error!(
             "failed to process event {:?} with rustc_commits handler: {:?}",
             event,
@@ -107,7 +152,8 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         );
     }
 
-    if let Err(e) = milestone_prs::handle(ctx, event).await {
+    if let Err(e) = metrics::track_handler("milestone_prs", milestone_prs::handle(ctx, event)).await
+    {
         log::error!(
             "failed to process event {:?} with milestone_prs handler: {:?}",
             event,
@@ -117,7 +163,12 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
 
     if let Some(rendered_link_config) = config.as_ref().ok().and_then(|c| c.rendered_link.as_ref())
     {
-        if let Err(e) = rendered_link::handle(ctx, event, rendered_link_config).await {
+        if let Err(e) = metrics::track_handler(
+            "rendered_link",
+            rendered_link::handle(ctx, event, rendered_link_config),
+        )
+        .await
+        {
             log::error!(
                 "failed to process event {:?} with rendered_link handler: {:?}",
                 event,
@@ -126,7 +177,7 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         }
     }
 
-    if let Err(e) = relnotes::handle(ctx, event).await {
+    if let Err(e) = metrics::track_handler("relnotes", relnotes::handle(ctx, event)).await {
         log::error!(
             "failed to process event {:?} with relnotes handler: {:?}",
             event,
@@ -135,7 +186,9 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
     }
 
     if config.as_ref().is_ok_and(|c| c.bot_pull_requests.is_some()) {
-        if let Err(e) = bot_pull_requests::handle(ctx, event).await {
+        if let Err(e) =
+            metrics::track_handler("bot_pull_requests", bot_pull_requests::handle(ctx, event)).await
+        {
             log::error!(
                 "failed to process event {:?} with bot_pull_requests handler: {:?}",
                 event,
@@ -149,7 +202,12 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         .ok()
         .and_then(|c| c.review_submitted.as_ref())
     {
-        if let Err(e) = review_submitted::handle(ctx, event, config).await {
+        if let Err(e) = metrics::track_handler(
+            "review_submitted",
+            review_submitted::handle(ctx, event, config),
+        )
+        .await
+        {
             log::error!(
                 "failed to process event {:?} with review_submitted handler: {:?}",
                 event,
@@ -163,7 +221,12 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         .ok()
         .and_then(|c| c.github_releases.as_ref())
     {
-        if let Err(e) = github_releases::handle(ctx, event, ghr_config).await {
+        if let Err(e) = metrics::track_handler(
+            "github_releases",
+            github_releases::handle(ctx, event, ghr_config),
+        )
+        .await
+        {
             log::error!(
                 "failed to process event {:?} with github_releases handler: {:?}",
                 event,
@@ -177,7 +240,12 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         .ok()
         .and_then(|c| c.merge_conflicts.as_ref())
     {
-        if let Err(e) = merge_conflicts::handle(ctx, event, conflict_config).await {
+        if let Err(e) = metrics::track_handler(
+            "merge_conflicts",
+            merge_conflicts::handle(ctx, event, conflict_config),
+        )
+        .await
+        {
             log::error!(
                 "failed to process event {:?} with merge_conflicts handler: {:?}",
                 event,
@@ -186,6 +254,21 @@ pub async fn handle(ctx: &Context, event: &Event) -> Vec<HandlerError> {
         }
     }
 
+    if let Some(ci_failures_config) = config.as_ref().ok().and_then(|c| c.ci_failures.as_ref()) {
+        if let Err(e) = metrics::track_handler(
+            "ci_failures",
+            ci_failures::handle(ctx, event, ci_failures_config),
+        )
+        .await
+        {
+            log::error!(
+                "failed to process event {:?} with ci_failures handler: {:?}",
+                event,
+                e
+            );
+        }
+    }
+
     errors
 }
 
@@ -202,7 +285,9 @@ macro_rules! issue_handlers {
                 Err(err) => errors.push(HandlerError::Message(err)),
                 Ok(Some(input)) => {
                     if let Some(config) = &config.$name {
-                        $name::handle_input(ctx, config, event, input).await.unwrap_or_else(|err| errors.push(HandlerError::Other(err)));
+                        metrics::track_handler(stringify!($name), $name::handle_input(ctx, config, event, input))
+                            .await
+                            .unwrap_or_else(|err| errors.push(err.into()));
                     } else {
                         errors.push(HandlerError::Message(format!(
                             "The feature `{}` is not enabled in this repository.\n\
@@ -232,6 +317,7 @@ issue_handlers! {
     notify_zulip,
     review_requested,
     pr_tracking,
+    remind,
     validate_config,
 }
 
@@ -260,8 +346,9 @@ macro_rules! command_handlers {
                     log::debug!("skipping event, issue was {:?}", e.action);
                     return;
                 }
-                Event::IssueComment(e) => {
-                    match e.action {
+                Event::IssueComment(IssueCommentEvent { action, .. })
+                | Event::PullRequestReviewComment(PullRequestReviewComment { action, .. }) => {
+                    match action {
                         IssueCommentAction::Created => {}
                         IssueCommentAction::Edited => {
                             if event.comment_from().is_none() {
@@ -275,20 +362,36 @@ macro_rules! command_handlers {
                         }
                         IssueCommentAction::Deleted => {
                             // don't execute commands again when comment is deleted
-                            log::debug!("skipping event, comment was {:?}", e.action);
+                            log::debug!("skipping event, comment was {:?}", action);
+                            return;
+                        }
+                    }
+                }
+                Event::PullRequestReview(e) => {
+                    match e.action {
+                        PullRequestReviewAction::Submitted => {}
+                        PullRequestReviewAction::Edited => {
+                            if event.comment_from().is_none() {
+                                log::debug!("skipping review edit without body changes");
+                                return;
+                            }
+                        }
+                        PullRequestReviewAction::Dismissed => {
+                            log::debug!("skipping event, review was {:?}", e.action);
                             return;
                         }
                     }
                 }
-                Event::Push(_) | Event::Create(_) => {
+                Event::Push(_) | Event::Create(_) | Event::CheckRun(_) | Event::CheckSuite(_) | Event::WorkflowRun(_) => {
                     log::debug!("skipping unsupported event");
                     return;
                 }
             }
 
-            let input = Input::new(&body, vec![&ctx.username, "triagebot"]);
+            let prefix = config.as_ref().ok().and_then(|config| config.command_prefix());
+            let input = Input::new(&body, vec![&ctx.username, "triagebot"], prefix);
             let commands = if let Some(previous) = event.comment_from() {
-                let prev_commands = Input::new(&previous, vec![&ctx.username, "triagebot"]).collect::<Vec<_>>();
+                let prev_commands = Input::new(&previous, vec![&ctx.username, "triagebot"], prefix).collect::<Vec<_>>();
                 input.filter(|cmd| !prev_commands.contains(cmd)).collect::<Vec<_>>()
             } else {
                 input.collect()
@@ -314,7 +417,12 @@ macro_rules! command_handlers {
                     }
                     return errors.push(HandlerError::Message(e.to_string()));
                 }
-                Err(e @ ConfigurationError::Toml(_)) => {
+                Err(
+                    e @ (ConfigurationError::Toml(_)
+                    | ConfigurationError::Extends { .. }
+                    | ConfigurationError::ExtendsInvalid(_)
+                    | ConfigurationError::ExtendsCycle(_)),
+                ) => {
                     return errors.push(HandlerError::Message(e.to_string()));
                 }
                 Err(e @ ConfigurationError::Http(_)) => {
@@ -327,9 +435,9 @@ macro_rules! command_handlers {
                     $(
                     Command::$enum(Ok(command)) => {
                         if let Some(config) = &config.$name {
-                            $name::handle_command(ctx, config, event, command)
+                            metrics::track_handler(stringify!($name), $name::handle_command(ctx, config, event, command))
                                 .await
-                                .unwrap_or_else(|err| errors.push(HandlerError::Other(err)));
+                                .unwrap_or_else(|err| errors.push(err.into()));
                         } else {
                             errors.push(HandlerError::Message(format!(
                                 "The feature `{}` is not enabled in this repository.\n\
@@ -347,6 +455,31 @@ macro_rules! command_handlers {
                             err
                         )));
                     })*
+                    // Help is always available, and lists the commands of the
+                    // whole configuration rather than a section of it.
+                    Command::Help(Ok(command)) => {
+                        metrics::track_handler("help", help::handle_command(ctx, config, event, command))
+                            .await
+                            .unwrap_or_else(|err| errors.push(err.into()));
+                    }
+                    Command::Help(Err(err)) => {
+                        errors.push(HandlerError::Message(format!(
+                            "Parsing help command in [comment]({}) failed: {}",
+                            event.html_url().expect("has html url"),
+                            err
+                        )));
+                    }
+                    Command::Unknown(err) => {
+                        let mut message = format!(
+                            "Unknown command `{}` in [comment]({}).",
+                            err.snippet(),
+                            event.html_url().expect("has html url"),
+                        );
+                        if let Some(suggestion) = &err.suggestion {
+                            write!(message, " Did you mean `@{} {suggestion}`?", ctx.username).unwrap();
+                        }
+                        errors.push(HandlerError::Message(message));
+                    }
                 }
             }
         }
@@ -356,7 +489,7 @@ macro_rules! command_handlers {
 // Handle commands in comments/issues body
 //
 // This is for handlers for commands parsed by the `parser` crate.
-// Each variant of `parser::command::Command` must be in this list,
+// Each variant of `parser::command::Command` except `Help` must be in this list,
 // preceded by the module containing the corresponding `handle_command` function
 command_handlers! {
     assign: Assign,
@@ -368,8 +501,12 @@ command_handlers! {
     major_change: Second,
     shortcut: Shortcut,
     close: Close,
+    reopen: Reopen,
+    lock: Lock,
+    milestone: Milestone,
     note: Note,
     transfer: Transfer,
+    remind: Remind,
 }
 
 pub struct Context {
@@ -380,4 +517,33 @@ pub struct Context {
     /// Represents the workqueue (assigned open PRs) of individual reviewers.
     /// tokio's RwLock is used to avoid deadlocks, since we run on a single-threaded tokio runtime.
     pub workqueue: Arc<tokio::sync::RwLock<ReviewerWorkqueue>>,
+    /// Set when running as a GitHub App, in which case events are handled
+    /// with a token for the installation that sent them.
+    pub github_app: Option<Arc<GithubApp>>,
+}
+
+impl Context {
+    /// Returns a context whose GitHub clients act as the given installation of
+    /// the GitHub App.
+    ///
+    /// Returns an error if no app is configured.
+    pub async fn for_installation(&self, installation_id: u64) -> anyhow::Result<Context> {
+        let app = self
+            .github_app
+            .as_ref()
+            .context("no GitHub App is configured")?;
+        let token = app.installation_token(installation_id).await?;
+        let octocrab = octocrab::OctocrabBuilder::new()
+            .personal_token(token.clone())
+            .build()
+            .context("failed to build octocrab client")?;
+        Ok(Context {
+            github: self.github.with_token(token),
+            db: self.db.clone(),
+            username: self.username.clone(),
+            octocrab,
+            workqueue: self.workqueue.clone(),
+            github_app: self.github_app.clone(),
+        })
+    }
 }
@@ -6,6 +6,10 @@
 //! * `@rustbot claim`: Assigns to the comment author.
 //! * `@rustbot release-assignment`: Removes the commenter's assignment.
 //! * `r? @user`: Assigns to the given user (PRs only).
+//! * `r? @user, @compiler`: Assigns a reviewer for each name (PRs only). The
+//!   names after the first need the `@`, unless they are `org/team` names.
+//! * `r? compiler !@user`: Assigns a reviewer from the team other than the
+//!   excluded users (PRs only).
 //!
 //! Note: this module does not handle review assignments issued from the
 //! GitHub "Assignees" dropdown menu
@@ -21,10 +25,11 @@
 //! the PR modifies.
 
 use crate::{
-    config::AssignConfig,
+    config::{self, AssignConfig, MessagesConfig},
     github::{self, Event, FileDiff, Issue, IssuesAction, Selection},
     handlers::{Context, GithubClient, IssuesEvent},
     interactions::EditIssueBody,
+    messages::{self, Message},
 };
 use anyhow::{bail, Context as _};
 use parser::command::assign::AssignCommand;
@@ -42,38 +47,6 @@ mod tests {
     mod tests_from_diff;
 }
 
-const NEW_USER_WELCOME_MESSAGE: &str = "Thanks for the pull request, and welcome! \
-The Rust team is excited to review your changes, and you should hear from {who} \
-some time within the next two weeks.";
-
-const CONTRIBUTION_MESSAGE: &str = "Please see [the contribution \
-instructions]({contributing_url}) for more information. Namely, in order to ensure the \
-minimum review times lag, PR authors and assigned reviewers should ensure that the review \
-label (`S-waiting-on-review` and `S-waiting-on-author`) stays updated, invoking these commands \
-when appropriate:
-
-- `@{bot} author`: the review is finished, PR author should check the comments and take action accordingly
-- `@{bot} review`: the author is ready for a review, this PR will be queued again in the reviewer's queue";
-
-const WELCOME_WITH_REVIEWER: &str = "@{assignee} (or someone else)";
-
-const WELCOME_WITHOUT_REVIEWER: &str = "@Mark-Simulacrum (NB. this repo may be misconfigured)";
-
-const RETURNING_USER_WELCOME_MESSAGE: &str = "r? @{assignee}
-
-{bot} has assigned @{assignee}.
-They will have a look at your PR within the next two weeks and either review your PR or \
-reassign to another reviewer.
-
-Use `r?` to explicitly pick a reviewer";
-
-const RETURNING_USER_WELCOME_MESSAGE_NO_REVIEWER: &str =
-    "@{author}: no appropriate reviewer found, use `r?` to override";
-
-const ON_VACATION_WARNING: &str = "{username} is on vacation.
-
-Please choose another assignee.";
-
 pub const SELF_ASSIGN_HAS_NO_CAPACITY: &str = "
 You have insufficient capacity to be assigned the pull request at this time. PR assignment has been reverted.
 
@@ -81,20 +54,6 @@ Please choose another assignee or increase your assignment limit.
 
 (see [documentation](https://forge.rust-lang.org/triagebot/pr-assignment-tracking.html))";
 
-pub const REVIEWER_HAS_NO_CAPACITY: &str = "
-`{username}` has insufficient capacity to be assigned the pull request at this time. PR assignment has been reverted.
-
-Please choose another assignee.
-
-(see [documentation](https://forge.rust-lang.org/triagebot/pr-assignment-tracking.html))";
-
-const NO_REVIEWER_HAS_CAPACITY: &str = "
-Could not find a reviewer with enough capacity to be assigned at this time. This is a problem.
-
-Please contact us on [#t-infra](https://rust-lang.zulipchat.com/#narrow/stream/242791-t-infra) on Zulip.
-
-cc: @jackh726 @apiraino";
-
 const REVIEWER_IS_PR_AUTHOR: &str = "Pull request author cannot be assigned as reviewer.
 
 Please choose another assignee.";
@@ -148,8 +107,14 @@ pub(super) async fn handle_input(
 
     // Don't auto-assign or welcome if the user manually set the assignee when opening.
     if event.issue.assignees.is_empty() {
-        let (assignee, from_comment) = determine_assignee(ctx, event, config, &diff).await?;
-        if assignee.as_deref() == Some("ghost") {
+        // The handler only gets its own section, but the messages are in another.
+        let repo_config = config::get(&ctx.github, &event.repository).await?;
+        let messages = repo_config.messages.as_ref();
+        let (assignees, from_comment) =
+            determine_assignee(ctx, event, config, messages, &diff).await?;
+        // Only the first reviewer is named in the welcome message.
+        let assignee = assignees.first();
+        if assignee.map(String::as_str) == Some("ghost") {
             // "ghost" is GitHub's placeholder account for deleted accounts.
             // It is used here as a convenient way to prevent assignment. This
             // is typically used for rollups or experiments where you don't
@@ -165,35 +130,32 @@ pub(super) async fn handle_input(
                 .is_new_contributor(&event.repository, &event.issue.user.login)
                 .await
         {
-            let who_text = match &assignee {
-                Some(assignee) => WELCOME_WITH_REVIEWER.replace("{assignee}", assignee),
-                None => WELCOME_WITHOUT_REVIEWER.to_string(),
-            };
-            let mut welcome = NEW_USER_WELCOME_MESSAGE.replace("{who}", &who_text);
-            if let Some(contrib) = &config.contributing_url {
-                welcome.push_str("\n\n");
-                welcome.push_str(
-                    &CONTRIBUTION_MESSAGE
-                        .replace("{contributing_url}", contrib)
-                        .replace("{bot}", &ctx.username),
-                );
-            }
-            Some(welcome)
+            Some(messages::render(
+                messages,
+                Message::NewUserWelcome,
+                serde_json::json!({
+                    "author": event.issue.user.login,
+                    "bot": ctx.username,
+                    "assignee": assignee,
+                    "contributing_url": config.contributing_url,
+                }),
+            ))
         } else if !from_comment {
-            let welcome = match &assignee {
-                Some(assignee) => RETURNING_USER_WELCOME_MESSAGE
-                    .replace("{assignee}", assignee)
-                    .replace("{bot}", &ctx.username),
-                None => RETURNING_USER_WELCOME_MESSAGE_NO_REVIEWER
-                    .replace("{author}", &event.issue.user.login),
-            };
-            Some(welcome)
+            Some(messages::render(
+                messages,
+                Message::ReturningUserWelcome,
+                serde_json::json!({
+                    "author": event.issue.user.login,
+                    "bot": ctx.username,
+                    "assignee": assignee,
+                }),
+            ))
         } else {
             // No welcome is posted if they are not new and they used `r?` in the opening body.
             None
         };
-        if let Some(assignee) = assignee {
-            set_assignee(&event.issue, &ctx.github, &assignee).await;
+        if !assignees.is_empty() {
+            set_assignees(&event.issue, &ctx.github, &assignees).await;
         }
 
         if let Some(welcome) = welcome {
@@ -211,11 +173,15 @@ pub(super) async fn handle_input(
 
 /// Finds the `r?` command in the PR body.
 ///
-/// Returns the name after the `r?` command, or None if not found.
-fn find_assign_command(ctx: &Context, event: &IssuesEvent) -> Option<String> {
-    let mut input = Input::new(&event.issue.body, vec![&ctx.username]);
+/// Returns the names after the `r?` command and the excluded users, or None
+/// if not found.
+fn find_assign_command(ctx: &Context, event: &IssuesEvent) -> Option<(Vec<String>, Vec<String>)> {
+    // Only `r?` is of interest, which doesn't need the command prefix.
+    let mut input = Input::new(&event.issue.body, vec![&ctx.username], None);
     input.find_map(|command| match command {
-        Command::Assign(Ok(AssignCommand::ReviewName { name })) => Some(name),
+        Command::Assign(Ok(AssignCommand::ReviewName { names, excluded })) => {
+            Some((names, excluded))
+        }
         _ => None,
     })
 }
@@ -224,38 +190,50 @@ fn is_self_assign(assignee: &str, pr_author: &str) -> bool {
     assignee.to_lowercase() == pr_author.to_lowercase()
 }
 
-/// Sets the assignee of a PR, alerting any errors.
-async fn set_assignee(issue: &Issue, github: &GithubClient, username: &str) {
+/// Sets the assignees of a PR, replacing the current ones, alerting any
+/// errors.
+async fn set_assignees(issue: &Issue, github: &GithubClient, usernames: &[String]) {
     // Don't re-assign if already assigned, e.g. on comment edit
-    if issue.contain_assignee(&username) {
+    if usernames
+        .iter()
+        .all(|username| issue.contain_assignee(username))
+    {
         log::trace!(
-            "ignoring assign PR {} to {}, already assigned",
+            "ignoring assign PR {} to {:?}, already assigned",
             issue.global_id(),
-            username,
+            usernames,
         );
         return;
     }
-    if let Err(err) = issue.set_assignee(github, &username).await {
-        log::warn!(
-            "failed to set assignee of PR {} to {}: {:?}",
-            issue.global_id(),
-            username,
-            err
-        );
-        if let Err(e) = issue
-            .post_comment(
-                github,
-                &format!(
-                    "Failed to set assignee to `{username}`: {err}\n\
-                     \n\
-                     > **Note**: Only org members with at least the repository \"read\" role, \
-                       users with write permissions, or people who have commented on the PR may \
-                       be assigned."
-                ),
-            )
-            .await
-        {
-            log::warn!("failed to post error comment: {e}");
+    for (i, username) in usernames.iter().enumerate() {
+        // The first one replaces the current assignees, the others join them.
+        let result = if i == 0 {
+            issue.set_assignee(github, username).await
+        } else {
+            issue.add_assignee(github, username).await
+        };
+        if let Err(err) = result {
+            log::warn!(
+                "failed to set assignee of PR {} to {}: {:?}",
+                issue.global_id(),
+                username,
+                err
+            );
+            if let Err(e) = issue
+                .post_comment(
+                    github,
+                    &format!(
+                        "Failed to set assignee to `{username}`: {err}\n\
+                         \n\
+                         > **Note**: Only org members with at least the repository \"read\" role, \
+                           users with write permissions, or people who have commented on the PR may \
+                           be assigned."
+                    ),
+                )
+                .await
+            {
+                log::warn!("failed to post error comment: {e}");
+            }
         }
     }
 }
@@ -265,29 +243,38 @@ async fn set_assignee(issue: &Issue, github: &GithubClient, username: &str) {
 ///
 /// Will also check if candidates have capacity in their work queue.
 ///
-/// Returns `(assignee, from_comment)` where `assignee` is who to assign to
-/// (or None if no assignee could be found). `from_comment` is a boolean
-/// indicating if the assignee came from an `r?` command (it is false if
-/// determined from the diff).
+/// Returns `(assignees, from_comment)` where `assignees` is who to assign to
+/// (empty if no assignee could be found, and only more than one if `r?`
+/// named several reviewers). `from_comment` is a boolean indicating if the
+/// assignees came from an `r?` command (it is false if determined from the
+/// diff).
 async fn determine_assignee(
     ctx: &Context,
     event: &IssuesEvent,
     config: &AssignConfig,
+    messages: Option<&MessagesConfig>,
     diff: &[FileDiff],
-) -> anyhow::Result<(Option<String>, bool)> {
+) -> anyhow::Result<(Vec<String>, bool)> {
     let db_client = ctx.db.get().await;
     let teams = crate::team_data::teams(&ctx.github).await?;
-    if let Some(name) = find_assign_command(ctx, event) {
-        if is_self_assign(&name, &event.issue.user.login) {
-            return Ok((Some(name.to_string()), true));
-        }
+    if let Some((names, excluded)) = find_assign_command(ctx, event) {
         // User included `r?` in the opening PR body.
-        match find_reviewer_from_names(&db_client, &teams, config, &event.issue, &[name]).await {
-            Ok(assignee) => return Ok((Some(assignee), true)),
+        match find_reviewers_from_names(
+            &db_client,
+            &teams,
+            config,
+            &event.issue,
+            &names,
+            &excluded,
+            &event.issue.user.login,
+        )
+        .await
+        {
+            Ok(assignees) => return Ok((assignees, true)),
             Err(e) => {
                 event
                     .issue
-                    .post_comment(&ctx.github, &e.to_string())
+                    .post_comment(&ctx.github, &e.comment(messages, &ctx.username))
                     .await?;
                 // Fall through below for normal diff detection.
             }
@@ -296,10 +283,18 @@ async fn determine_assignee(
     // Errors fall-through to try fallback group.
     match find_reviewers_from_diff(config, diff) {
         Ok(candidates) if !candidates.is_empty() => {
-            match find_reviewer_from_names(&db_client, &teams, config, &event.issue, &candidates)
-                .await
+            match find_reviewer_from_names(
+                &db_client,
+                &teams,
+                config,
+                &event.issue,
+                &candidates,
+                &[],
+                &[],
+            )
+            .await
             {
-                Ok(assignee) => return Ok((Some(assignee), false)),
+                Ok(assignee) => return Ok((vec![assignee], false)),
                 Err(FindReviewerError::TeamNotFound(team)) => log::warn!(
                     "team {team} not found via diff from PR {}, \
                     is there maybe a misconfigured group?",
@@ -311,7 +306,8 @@ async fn determine_assignee(
                     | e @ FindReviewerError::NoReviewerHasCapacity
                     | e @ FindReviewerError::ReviewerHasNoCapacity { .. }
                     | e @ FindReviewerError::ReviewerIsPrAuthor { .. }
-                    | e @ FindReviewerError::ReviewerAlreadyAssigned { .. },
+                    | e @ FindReviewerError::ReviewerAlreadyAssigned { .. }
+                    | e @ FindReviewerError::ReviewerExcluded { .. },
                 ) => log::trace!(
                     "no reviewer could be determined for PR {}: {e}",
                     event.issue.global_id()
@@ -336,8 +332,10 @@ async fn determine_assignee(
     }
 
     if let Some(fallback) = config.adhoc_groups.get("fallback") {
-        match find_reviewer_from_names(&db_client, &teams, config, &event.issue, fallback).await {
-            Ok(assignee) => return Ok((Some(assignee), false)),
+        match find_reviewer_from_names(&db_client, &teams, config, &event.issue, fallback, &[], &[])
+            .await
+        {
+            Ok(assignee) => return Ok((vec![assignee], false)),
             Err(e) => {
                 log::trace!(
                     "failed to select from fallback group for PR {}: {e}",
@@ -346,7 +344,7 @@ async fn determine_assignee(
             }
         }
     }
-    Ok((None, false))
+    Ok((Vec::new(), false))
 }
 
 /// Returns a list of candidate reviewers to use based on which files were changed.
@@ -451,6 +449,9 @@ pub(super) async fn handle_command(
     }
 
     let issue = event.issue().unwrap();
+    // The handler only gets its own section, but the messages are in another.
+    let repo_config = config::get(&ctx.github, event.repo()).await?;
+    let messages = repo_config.messages.as_ref();
     if issue.is_pr() {
         if !issue.is_open() {
             issue
@@ -458,8 +459,8 @@ pub(super) async fn handle_command(
                 .await?;
             return Ok(());
         }
-        let username = match cmd {
-            AssignCommand::Own => event.user().login.clone(),
+        let usernames = match cmd {
+            AssignCommand::Own => vec![event.user().login.clone()],
             AssignCommand::User { username } => {
                 // Allow users on vacation to assign themselves to a PR, but not anyone else.
                 if config.is_on_vacation(&username)
@@ -469,12 +470,16 @@ pub(super) async fn handle_command(
                     issue
                         .post_comment(
                             &ctx.github,
-                            &ON_VACATION_WARNING.replace("{username}", &username),
+                            &messages::render(
+                                messages,
+                                Message::OnVacation,
+                                serde_json::json!({ "username": username, "bot": ctx.username }),
+                            ),
                         )
                         .await?;
                     return Ok(());
                 }
-                username
+                vec![username]
             }
             AssignCommand::Release => {
                 log::trace!(
@@ -483,7 +488,7 @@ pub(super) async fn handle_command(
                 );
                 return Ok(());
             }
-            AssignCommand::ReviewName { name } => {
+            AssignCommand::ReviewName { names, excluded } => {
                 if config.owners.is_empty() {
                     // To avoid conflicts with the highfive bot while transitioning,
                     // r? is ignored if `owners` is not configured in triagebot.toml.
@@ -502,27 +507,29 @@ pub(super) async fn handle_command(
                     return Ok(());
                 }
                 let db_client = ctx.db.get().await;
-                if is_self_assign(&name, &event.user().login) {
-                    // let work_queue = has_user_capacity(&db_client, &name).await;
-                    // if work_queue.is_err() {
-                    //     // NOTE: disabled for now, just log
-                    //     log::warn!(
-                    //         "[#{}] PR self-assign failed, DB reported that user {} has no review capacity. Ignoring.",
-                    //         issue.number,
-                    //         name
-                    //     );
-                    //     // issue
-                    //     //     .post_comment(
-                    //     //         &ctx.github,
-                    //     //         &REVIEWER_HAS_NO_CAPACITY.replace("{username}", &name),
-                    //     //     )
-                    //     //     .await?;
-                    //     // return Ok(());
-                    // }
-
-                    name.to_string()
-                } else {
-                    let teams = crate::team_data::teams(&ctx.github).await?;
+                let teams = crate::team_data::teams(&ctx.github).await?;
+                let mut reviewer_names = Vec::new();
+                for name in names {
+                    if is_self_assign(&name, &event.user().login) {
+                        // let work_queue = has_user_capacity(&db_client, &name).await;
+                        // if work_queue.is_err() {
+                        //     // NOTE: disabled for now, just log
+                        //     log::warn!(
+                        //         "[#{}] PR self-assign failed, DB reported that user {} has no review capacity. Ignoring.",
+                        //         issue.number,
+                        //         name
+                        //     );
+                        //     // issue
+                        //     //     .post_comment(
+                        //     //         &ctx.github,
+                        //     //         &REVIEWER_HAS_NO_CAPACITY.replace("{username}", &name),
+                        //     //     )
+                        //     //     .await?;
+                        //     // return Ok(());
+                        // }
+                        reviewer_names.push(name);
+                        continue;
+                    }
                     // remove "t-" or "T-" prefixes before checking if it's a team name
                     let team_name = name.trim_start_matches("t-").trim_start_matches("T-");
                     // Determine if assignee is a team. If yes, add the corresponding GH label.
@@ -539,29 +546,34 @@ pub(super) async fn handle_command(
                             }
                         }
                     }
+                    reviewer_names.push(team_name.to_string());
+                }
 
-                    match find_reviewer_from_names(
-                        &db_client,
-                        &teams,
-                        config,
-                        issue,
-                        &[team_name.to_string()],
-                    )
-                    .await
-                    {
-                        Ok(assignee) => assignee,
-                        Err(e) => {
-                            issue.post_comment(&ctx.github, &e.to_string()).await?;
-                            return Ok(());
-                        }
+                match find_reviewers_from_names(
+                    &db_client,
+                    &teams,
+                    config,
+                    issue,
+                    &reviewer_names,
+                    &excluded,
+                    &event.user().login,
+                )
+                .await
+                {
+                    Ok(assignees) => assignees,
+                    Err(e) => {
+                        issue
+                            .post_comment(&ctx.github, &e.comment(messages, &ctx.username))
+                            .await?;
+                        return Ok(());
                     }
                 }
             }
         };
 
-        // This user is validated and can accept the PR
-        set_assignee(issue, &ctx.github, &username).await;
-        // This PR will now be registered in the reviewer's work queue
+        // These users are validated and can accept the PR
+        set_assignees(issue, &ctx.github, &usernames).await;
+        // This PR will now be registered in each reviewer's work queue
         // by the `pr_tracking` handler
         return Ok(());
     }
@@ -672,10 +684,45 @@ pub enum FindReviewerError {
     ReviewerIsPrAuthor { username: String },
     /// Requested reviewer is already assigned to that PR
     ReviewerAlreadyAssigned { username: String },
+    /// Requested reviewer was excluded with `!@username`
+    ReviewerExcluded { username: String },
 }
 
 impl std::error::Error for FindReviewerError {}
 
+impl FindReviewerError {
+    /// The configurable message for this error, with its variables (except
+    /// `bot`), if there is one.
+    fn message(&self) -> Option<(Message, serde_json::Value)> {
+        match self {
+            FindReviewerError::ReviewerHasNoCapacity { username } => Some((
+                Message::ReviewerHasNoCapacity,
+                serde_json::json!({ "username": username }),
+            )),
+            FindReviewerError::NoReviewerHasCapacity => {
+                Some((Message::NoReviewerHasCapacity, serde_json::json!({})))
+            }
+            FindReviewerError::ReviewerOnVacation { username } => Some((
+                Message::OnVacation,
+                serde_json::json!({ "username": username }),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The comment reporting this error, using the repository's configured
+    /// `messages`.
+    fn comment(&self, messages: Option<&MessagesConfig>, bot: &str) -> String {
+        match self.message() {
+            Some((message, mut vars)) => {
+                vars["bot"] = bot.into();
+                messages::render(messages, message, vars)
+            }
+            None => self.to_string(),
+        }
+    }
+}
+
 impl fmt::Display for FindReviewerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
@@ -701,24 +748,17 @@ impl fmt::Display for FindReviewerError {
                 write!(
                     f,
                     "Could not assign reviewer from: `{}`.\n\
-                     User(s) `{}` are either the PR author, already assigned, or on vacation. \
-                     Please use `r?` to specify someone else to assign.",
+                     User(s) `{}` are either the PR author, already assigned, excluded, \
+                     or on vacation. Please use `r?` to specify someone else to assign.",
                     initial.join(","),
                     filtered.join(","),
                 )
             }
-            FindReviewerError::ReviewerHasNoCapacity { username } => {
-                write!(
-                    f,
-                    "{}",
-                    REVIEWER_HAS_NO_CAPACITY.replace("{username}", username)
-                )
-            }
-            FindReviewerError::NoReviewerHasCapacity => {
-                write!(f, "{}", NO_REVIEWER_HAS_CAPACITY)
-            }
-            FindReviewerError::ReviewerOnVacation { username } => {
-                write!(f, "{}", ON_VACATION_WARNING.replace("{username}", username))
+            FindReviewerError::ReviewerHasNoCapacity { .. }
+            | FindReviewerError::NoReviewerHasCapacity
+            | FindReviewerError::ReviewerOnVacation { .. } => {
+                let (message, vars) = self.message().unwrap();
+                write!(f, "{}", messages::render_default(message, vars))
             }
             FindReviewerError::ReviewerIsPrAuthor { username } => {
                 write!(
@@ -734,6 +774,14 @@ impl fmt::Display for FindReviewerError {
                     REVIEWER_ALREADY_ASSIGNED.replace("{username}", username)
                 )
             }
+            FindReviewerError::ReviewerExcluded { username } => {
+                write!(
+                    f,
+                    "Requested reviewer `{username}` is excluded with `!@{username}`.\n\
+                     \n\
+                     Please choose another assignee."
+                )
+            }
         }
     }
 }
@@ -744,14 +792,19 @@ impl fmt::Display for FindReviewerError {
 /// `@octocat`, or names from the owners map. It can contain GitHub usernames,
 /// auto-assign groups, or rust-lang team names. It must have at least one
 /// entry.
+///
+/// `chosen` are the reviewers already picked for other `r?` names, who are
+/// not picked again, and `excluded` the users excluded with `!@user`.
 async fn find_reviewer_from_names(
     _db: &DbClient,
     teams: &Teams,
     config: &AssignConfig,
     issue: &Issue,
     names: &[String],
+    chosen: &[String],
+    excluded: &[String],
 ) -> Result<String, FindReviewerError> {
-    let candidates = candidate_reviewers_from_names(teams, config, issue, names)?;
+    let candidates = candidate_reviewers_from_names(teams, config, issue, names, chosen, excluded)?;
     // This uses a relatively primitive random choice algorithm.
     // GitHub's CODEOWNERS supports much more sophisticated options, such as:
     //
@@ -811,12 +864,56 @@ async fn find_reviewer_from_names(
         .to_string())
 }
 
+/// Picks a reviewer for each of the `r?` names, each a different user.
+///
+/// A name that is the `requester` themselves is assigned as is, like with a
+/// single `r?`. Every reviewer is checked like in [`find_reviewer_from_names`].
+async fn find_reviewers_from_names(
+    db: &DbClient,
+    teams: &Teams,
+    config: &AssignConfig,
+    issue: &Issue,
+    names: &[String],
+    excluded: &[String],
+    requester: &str,
+) -> Result<Vec<String>, FindReviewerError> {
+    let mut reviewers: Vec<String> = Vec::new();
+    for name in names {
+        let reviewer = if is_self_assign(name, requester) {
+            name.clone()
+        } else {
+            find_reviewer_from_names(
+                db,
+                teams,
+                config,
+                issue,
+                std::slice::from_ref(name),
+                &reviewers,
+                excluded,
+            )
+            .await?
+        };
+        if !reviewers
+            .iter()
+            .any(|r| r.to_lowercase() == reviewer.to_lowercase())
+        {
+            reviewers.push(reviewer);
+        }
+    }
+    Ok(reviewers)
+}
+
 /// Returns a list of candidate usernames (from relevant teams) to choose as a reviewer.
+///
+/// Users in `chosen` are filtered out like the current assignees, and so are
+/// the `excluded` ones.
 fn candidate_reviewers_from_names<'a>(
     teams: &'a Teams,
     config: &'a AssignConfig,
     issue: &Issue,
     names: &'a [String],
+    chosen: &[String],
+    excluded: &[String],
 ) -> Result<HashSet<&'a str>, FindReviewerError> {
     // Set of candidate usernames to choose from. This uses a set to
     // deduplicate entries so that someone in multiple teams isn't
@@ -843,7 +940,11 @@ fn candidate_reviewers_from_names<'a>(
         let is_already_assigned = issue
             .assignees
             .iter()
-            .any(|assignee| name_lower == assignee.login.to_lowercase());
+            .any(|assignee| name_lower == assignee.login.to_lowercase())
+            || chosen.iter().any(|user| name_lower == user.to_lowercase());
+        let is_excluded = excluded
+            .iter()
+            .any(|user| name_lower == user.to_lowercase());
 
         // Record the reason why the candidate was filtered out
         let reason = {
@@ -859,12 +960,17 @@ fn candidate_reviewers_from_names<'a>(
                 Some(FindReviewerError::ReviewerAlreadyAssigned {
                     username: name.to_string(),
                 })
+            } else if is_excluded {
+                Some(FindReviewerError::ReviewerExcluded {
+                    username: name.to_string(),
+                })
             } else {
                 None
             }
         };
 
-        let can_be_assigned = !is_pr_author && !is_on_vacation && !is_already_assigned;
+        let can_be_assigned =
+            !is_pr_author && !is_on_vacation && !is_already_assigned && !is_excluded;
         if !can_be_assigned {
             filtered.push(name.to_string());
             filtered_debug.insert(name.to_string(), reason);
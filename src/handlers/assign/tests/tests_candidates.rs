@@ -13,7 +13,7 @@ fn test_from_names(
     let (teams, config, issue) = convert_simplified(teams, config, issue);
     let names: Vec<_> = names.iter().map(|n| n.to_string()).collect();
     match (
-        candidate_reviewers_from_names(&teams, &config, &issue, &names),
+        candidate_reviewers_from_names(&teams, &config, &issue, &names, &[], &[]),
         expected,
     ) {
         (Ok(candidates), Ok(expected)) => {
@@ -134,6 +134,67 @@ fn candidate_filtered_author_only_candidate() {
     );
 }
 
+#[test]
+fn candidate_filtered_chosen() {
+    // Reviewers already picked for another `r?` name are not picked again.
+    let config = toml::toml!(
+        [adhoc_groups]
+        compiler = ["nikomatsakis", "pnkfelix"]
+    );
+    let issue = generic_issue("octocat", "rust-lang/rust");
+    let (teams, config, issue) = convert_simplified(None, config, issue);
+    let names = vec!["compiler".to_string()];
+    let candidates =
+        candidate_reviewers_from_names(&teams, &config, &issue, &names, &["PNKfelix".into()], &[])
+            .unwrap();
+    assert_eq!(candidates, HashSet::from(["nikomatsakis"]));
+    assert_eq!(
+        candidate_reviewers_from_names(
+            &teams,
+            &config,
+            &issue,
+            &names,
+            &["pnkfelix".into(), "nikomatsakis".into()],
+            &[]
+        ),
+        Err(FindReviewerError::AllReviewersFiltered {
+            initial: names.clone(),
+            filtered: vec!["nikomatsakis".to_string(), "pnkfelix".to_string()],
+        })
+    );
+}
+
+#[test]
+fn candidate_filtered_excluded() {
+    // Users excluded with `!@user` are not picked.
+    let teams = toml::toml!(compiler = ["nikomatsakis", "pnkfelix"]);
+    let config = toml::toml!(
+        [adhoc_groups]
+        fallback = ["@octocat"]
+    );
+    let issue = generic_issue("octocat", "rust-lang/rust");
+    let (teams, config, issue) = convert_simplified(Some(teams), config, issue);
+    let names = vec!["compiler".to_string()];
+    let candidates =
+        candidate_reviewers_from_names(&teams, &config, &issue, &names, &[], &["pnkfelix".into()])
+            .unwrap();
+    assert_eq!(candidates, HashSet::from(["nikomatsakis"]));
+    assert_eq!(
+        candidate_reviewers_from_names(
+            &teams,
+            &config,
+            &issue,
+            &names,
+            &[],
+            &["nikomatsakis".into(), "pnkfelix".into()]
+        ),
+        Err(FindReviewerError::AllReviewersFiltered {
+            initial: names.clone(),
+            filtered: vec!["nikomatsakis".to_string(), "pnkfelix".to_string()],
+        })
+    );
+}
+
 #[test]
 fn candidate_filtered_author() {
     // Filter out the author from the candidates.
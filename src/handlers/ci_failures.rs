@@ -0,0 +1,190 @@
+//! Relabels PRs when their CI fails.
+//!
+//! When a check run, check suite or workflow run completes with a failure on
+//! the head commit of a PR, this swaps the PR's labels according to the
+//! `[ci-failures]` config (typically from `S-waiting-on-review` to
+//! `S-waiting-on-author`) and posts a comment listing the failing checks.
+//!
+//! The event is only acted on if the PR has one of the labels to remove, so
+//! the several CI events GitHub sends for a single failure only update the PR
+//! once.
+//!
+//! CI payloads only carry the PR number, so the PR is fetched here, once the
+//! event is known to be a failure in a repository with `[ci-failures]`.
+
+use crate::{
+    config::CiFailuresConfig,
+    github::{
+        CheckConclusion, CheckRun, CheckRunAction, CheckRunEvent, CheckSuiteAction,
+        CheckSuiteEvent, Event, Label, PullRequestDetails, WorkflowRunAction, WorkflowRunEvent,
+    },
+    handlers::Context,
+};
+use std::fmt::Write as _;
+use tracing as log;
+
+pub(super) async fn handle(
+    ctx: &Context,
+    event: &Event,
+    config: &CiFailuresConfig,
+) -> anyhow::Result<()> {
+    let (head_sha, conclusion, pull_requests) = match event {
+        Event::CheckRun(CheckRunEvent {
+            action: CheckRunAction::Completed,
+            check_run,
+            ..
+        }) => (
+            &check_run.head_sha,
+            check_run.conclusion,
+            &check_run.pull_requests,
+        ),
+        Event::CheckSuite(CheckSuiteEvent {
+            action: CheckSuiteAction::Completed,
+            check_suite,
+            ..
+        }) => (
+            &check_suite.head_sha,
+            check_suite.conclusion,
+            &check_suite.pull_requests,
+        ),
+        Event::WorkflowRun(WorkflowRunEvent {
+            action: WorkflowRunAction::Completed,
+            workflow_run,
+            ..
+        }) => (
+            &workflow_run.head_sha,
+            workflow_run.conclusion,
+            &workflow_run.pull_requests,
+        ),
+        _ => return Ok(()),
+    };
+    if !conclusion.is_some_and(CheckConclusion::is_failure) {
+        return Ok(());
+    }
+    let Some(pr) = pull_requests.first() else {
+        return Ok(());
+    };
+    let mut issue = event.repo().get_issue(&ctx.github, pr.number).await?;
+    issue.pull_request = Some(PullRequestDetails::new());
+    if !issue.is_open() {
+        return Ok(());
+    }
+    if issue.head.as_ref().map(|head| &head.sha) != Some(head_sha) {
+        log::debug!(
+            "ignoring CI failure on {head_sha} for {}, which is no longer its head",
+            issue.global_id()
+        );
+        return Ok(());
+    }
+    if !config.remove.is_empty()
+        && !issue
+            .labels()
+            .iter()
+            .any(|label| config.remove.contains(&label.name))
+    {
+        return Ok(());
+    }
+
+    let check_runs = event.repo().get_check_runs(&ctx.github, head_sha).await?;
+    let failing = failing_checks(&check_runs, &config.required_checks);
+    if failing.is_empty() {
+        return Ok(());
+    }
+
+    for label in &config.remove {
+        issue.remove_label(&ctx.github, label).await?;
+    }
+    let labels = config
+        .add
+        .iter()
+        .map(|name| Label { name: name.clone() })
+        .collect();
+    issue.add_labels(&ctx.github, labels).await?;
+
+    if config.comment {
+        issue
+            .post_comment(&ctx.github, &failure_comment(head_sha, &failing))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Returns the completed check runs that failed, restricted to
+/// `required_checks` unless it is empty.
+fn failing_checks<'a>(check_runs: &'a [CheckRun], required_checks: &[String]) -> Vec<&'a CheckRun> {
+    check_runs
+        .iter()
+        .filter(|run| run.conclusion.is_some_and(CheckConclusion::is_failure))
+        .filter(|run| required_checks.is_empty() || required_checks.contains(&run.name))
+        .collect()
+}
+
+fn failure_comment(head_sha: &str, failing: &[&CheckRun]) -> String {
+    let mut comment = format!("The following checks failed on {head_sha}:\n\n");
+    for run in failing {
+        match &run.html_url {
+            Some(url) => writeln!(comment, "- [{}]({url})", run.name).unwrap(),
+            None => writeln!(comment, "- {}", run.name).unwrap(),
+        }
+    }
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_run(name: &str, conclusion: Option<CheckConclusion>) -> CheckRun {
+        CheckRun {
+            id: 1,
+            name: name.to_string(),
+            head_sha: "abc".to_string(),
+            conclusion,
+            html_url: Some(format!("https://example.com/{name}")),
+            pull_requests: vec![],
+        }
+    }
+
+    #[test]
+    fn failing_checks_any() {
+        let runs = [
+            check_run("tidy", Some(CheckConclusion::Failure)),
+            check_run("x86_64", Some(CheckConclusion::Success)),
+            check_run("aarch64", None),
+            check_run("docs", Some(CheckConclusion::TimedOut)),
+            check_run("skipped", Some(CheckConclusion::Cancelled)),
+        ];
+        let names: Vec<_> = failing_checks(&runs, &[])
+            .iter()
+            .map(|run| run.name.as_str())
+            .collect();
+        assert_eq!(names, ["tidy", "docs"]);
+    }
+
+    #[test]
+    fn failing_checks_required() {
+        let runs = [
+            check_run("tidy", Some(CheckConclusion::Failure)),
+            check_run("optional", Some(CheckConclusion::Failure)),
+        ];
+        let names: Vec<_> = failing_checks(&runs, &["tidy".to_string()])
+            .iter()
+            .map(|run| run.name.as_str())
+            .collect();
+        assert_eq!(names, ["tidy"]);
+        assert!(failing_checks(&runs, &["x86_64".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn comment_lists_checks() {
+        let tidy = check_run("tidy", Some(CheckConclusion::Failure));
+        let mut docs = check_run("docs", Some(CheckConclusion::Failure));
+        docs.html_url = None;
+        assert_eq!(
+            failure_comment("abc", &[&tidy, &docs]),
+            "The following checks failed on abc:\n\n\
+             - [tidy](https://example.com/tidy)\n\
+             - docs\n"
+        );
+    }
+}
@@ -1,6 +1,10 @@
 //! Allows to close an issue or a PR
 
-use crate::{config::CloseConfig, github::Event, handlers::Context, interactions::ErrorComment};
+use crate::{
+    config::CloseConfig,
+    github::Event,
+    handlers::{Context, UserError},
+};
 use parser::command::close::CloseCommand;
 
 pub(super) async fn handle_command(
@@ -16,9 +20,7 @@ pub(super) async fn handle_command(
         .await
         .unwrap_or(false);
     if !is_team_member {
-        let cmnt = ErrorComment::new(&issue, "Only team members can close issues.");
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError("Only team members can close issues.".into()).into());
     }
     issue.close(&ctx.github).await?;
     Ok(())
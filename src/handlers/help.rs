@@ -0,0 +1,324 @@
+//! Purpose: Allow anyone to get a list of the commands enabled in a repository
+//! with `@bot help`.
+//!
+//! Parsing is done in the `parser::command::help` module.
+
+use crate::{config::Config, github::Event, handlers::Context};
+use parser::command::help::HelpCommand;
+use std::fmt::Write;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &Config,
+    event: &Event,
+    _cmd: HelpCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    issue
+        .post_comment(&ctx.github, &help_text(config, &ctx.username))
+        .await?;
+    Ok(())
+}
+
+/// Lists the commands enabled by `config`, with their syntax and who may use
+/// them.
+fn help_text(config: &Config, bot: &str) -> String {
+    let mut commands = Vec::new();
+    let team_only = "Only team members can use this.";
+
+    if let Some(relabel) = &config.relabel {
+        let mut text = format!(
+            "`@{bot} label +<label> -<label>`: adds and removes labels. \
+             Team members can change any label"
+        );
+        let allowed: Vec<_> = relabel
+            .allow_unauthenticated
+            .iter()
+            .filter(|pattern| !pattern.starts_with('!'))
+            .map(|pattern| format!("`{pattern}`"))
+            .collect();
+        let denied: Vec<_> = relabel
+            .allow_unauthenticated
+            .iter()
+            .filter_map(|pattern| pattern.strip_prefix('!'))
+            .map(|pattern| format!("`{pattern}`"))
+            .collect();
+        if allowed.is_empty() {
+            text.push('.');
+        } else {
+            write!(text, ", anyone else those matching {}", allowed.join(", ")).unwrap();
+            if !denied.is_empty() {
+                write!(text, " but not {}", denied.join(", ")).unwrap();
+            }
+            text.push('.');
+        }
+        commands.push(text);
+    }
+    if let Some(assign) = &config.assign {
+        commands.push(format!(
+            "`@{bot} claim`, `@{bot} release-assignment` or `@{bot} assign @<user>`: \
+             assigns yourself, unassigns, or assigns someone else. \
+             Only team members can assign other users or release someone else's assignment."
+        ));
+        if !assign.owners.is_empty() {
+            commands.push(
+                "`r? @<user>` or `r? <team or group>`: on pull requests, \
+                 picks a reviewer from the given user, team or reviewer group. \
+                 `r? @<user>, @<team>` picks one for each of them, \
+                 and `r? <team> !@<user>` never picks the excluded user."
+                    .to_string(),
+            );
+        }
+    }
+    if let Some(ping) = &config.ping {
+        let mut teams: Vec<_> = ping
+            .teams
+            .iter()
+            .map(|(team, cfg)| {
+                let mut aliases: Vec<_> = cfg.alias.iter().map(|a| format!("`{a}`")).collect();
+                aliases.sort();
+                if aliases.is_empty() {
+                    format!("`{team}`")
+                } else {
+                    format!("`{team}` (or {})", aliases.join(", "))
+                }
+            })
+            .collect();
+        teams.sort();
+        commands.push(format!(
+            "`@{bot} ping <team>`: notifies a team. {team_only} Teams: {}.",
+            teams.join(", ")
+        ));
+    }
+    if let Some(nominate) = &config.nominate {
+        let mut teams: Vec<_> = nominate.teams.keys().map(|t| format!("`{t}`")).collect();
+        teams.sort();
+        commands.push(format!(
+            "`@{bot} nominate <team>`, `@{bot} beta-nominate <team>` or \
+             `@{bot} beta-accept` (or `beta-approve`): \
+             nominates for discussion or for a beta backport, or accepts a beta nomination. \
+             {team_only} Teams: {}.",
+            teams.join(", ")
+        ));
+    }
+    if let Some(prioritize) = &config.prioritize {
+        commands.push(format!(
+            "`@{bot} prioritize`: adds the `{}` label to request prioritization.",
+            prioritize.label
+        ));
+    }
+    if config.glacier.is_some() {
+        commands.push(format!(
+            "`@{bot} glacier \"<gist url>\"`: adds the code of the playground gist to glacier \
+             as an ICE test. {team_only}"
+        ));
+    }
+    if config.major_change.is_some() {
+        commands.push(format!(
+            "`@{bot} second` (or `seconded`): seconds a major change proposal. {team_only}"
+        ));
+    }
+    if config.shortcut.is_some() {
+        commands.push(format!(
+            "`@{bot} ready` (or `review`), `@{bot} author` or `@{bot} blocked`: \
+             on pull requests, marks the PR as waiting on review, waiting on the author, \
+             or blocked."
+        ));
+    }
+    if config.note.is_some() {
+        commands.push(format!(
+            "`@{bot} note <title>` or `@{bot} note remove <title>`, \
+             with the title quoted if it has several words: \
+             adds or removes a link to the comment in the summary of the issue description."
+        ));
+    }
+    if config.transfer.is_some() {
+        commands.push(format!(
+            "`@{bot} transfer <repo>`: transfers the issue to another repository \
+             in the organization. {team_only}"
+        ));
+    }
+    if config.remind.is_some() {
+        commands.push(format!(
+            "`@{bot} remind me in <amount> <minutes|hours|days|weeks> [message]`, \
+             `@{bot} remind @<user> on <YYYY-MM-DD> [message]` or `@{bot} reminders`: \
+             sets a reminder about the issue, or lists the pending ones. \
+             Only team members can remind other users."
+        ));
+    }
+    if let Some(milestone) = &config.milestone {
+        let mut text = format!(
+            "`@{bot} milestone <title>` or `@{bot} milestone remove`: \
+             sets or removes the milestone of the issue or PR"
+        );
+        if milestone.create_missing {
+            text.push_str(", creating it if needed");
+        }
+        write!(text, ". {team_only}").unwrap();
+        commands.push(text);
+    }
+    if config.close.is_some() {
+        commands.push(format!(
+            "`@{bot} close`: closes the issue or PR. {team_only}"
+        ));
+    }
+    if config.reopen.is_some() {
+        commands.push(format!(
+            "`@{bot} reopen`: reopens the issue or PR. {team_only}"
+        ));
+    }
+    if config.lock.is_some() {
+        commands.push(format!(
+            "`@{bot} lock [off-topic|too-heated|resolved|spam]` or `@{bot} unlock`: \
+             locks the conversation, with an optional reason, or unlocks it. {team_only}"
+        ));
+    }
+    commands.push(format!("`@{bot} help`: shows this message."));
+
+    let mut text = "These commands are available in this repository:\n".to_string();
+    for command in commands {
+        write!(text, "\n- {command}").unwrap();
+    }
+    if let Some(prefix) = config.command_prefix() {
+        write!(
+            text,
+            "\n\nCommands can also be written on their own line starting with `{prefix}` \
+             instead of `@{bot} `, e.g. `{prefix}help`."
+        )
+        .unwrap();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::command::Input;
+
+    fn help(toml: &str) -> String {
+        help_text(&toml::from_str(toml).unwrap(), "rustbot")
+    }
+
+    #[test]
+    fn only_help_by_default() {
+        assert_eq!(
+            help(""),
+            "These commands are available in this repository:\n\n\
+             - `@rustbot help`: shows this message."
+        );
+    }
+
+    #[test]
+    fn lists_enabled_commands() {
+        let text = help(
+            r#"
+            [relabel]
+            allow-unauthenticated = ["T-*", "A-*", "!I-*nominated"]
+
+            [ping.compiler]
+            message = "ping!"
+            alias = ["compiler-team", "rustc"]
+            [ping.wg-meta]
+            message = "ping!"
+
+            [nominate.teams]
+            lang = "T-lang"
+            compiler = "T-compiler"
+
+            [shortcut]
+            [close]
+
+            [commands]
+            prefix = "/"
+            "#,
+        );
+        assert!(text.contains(
+            "Team members can change any label, anyone else those matching `T-*`, `A-*` \
+             but not `I-*nominated`."
+        ));
+        assert!(text.contains("Teams: `compiler` (or `compiler-team`, `rustc`), `wg-meta`."));
+        assert!(text.contains("Teams: `compiler`, `lang`."));
+        assert!(text.contains("`@rustbot ready` (or `review`)"));
+        assert!(text.contains("`@rustbot close`: closes the issue or PR."));
+        assert!(!text.contains("claim"));
+        assert!(!text.contains("transfer"));
+        assert!(text.ends_with(
+            "- `@rustbot help`: shows this message.\n\nCommands can also be written on their \
+             own line starting with `/` instead of `@rustbot `, e.g. `/help`."
+        ));
+    }
+
+    /// Replaces the `<placeholders>` and `[optional parts]` of a command in
+    /// the help by example values.
+    fn example(command: &str) -> String {
+        let mut example = String::new();
+        let mut rest = command;
+        while let Some(start) = rest.find(['<', '[']) {
+            let end = start + rest[start..].find(['>', ']']).unwrap();
+            example.push_str(&rest[..start]);
+            // Alternatives are separated by `|`.
+            example.push_str(match rest[start + 1..end].split('|').next().unwrap() {
+                "label" => "T-compiler",
+                "user" => "octocat",
+                "team" | "team or group" => "compiler",
+                "gist url" => "https://gist.github.com/octocat/1234",
+                "title" => "1.85.0",
+                "repo" => "rust",
+                "amount" => "3",
+                "YYYY-MM-DD" => "2026-11-01",
+                "message" => "to look at this again",
+                literal => literal,
+            });
+            rest = &rest[end + 1..];
+        }
+        example.push_str(rest);
+        example
+    }
+
+    #[test]
+    fn commands_match_grammar() {
+        let text = help(
+            r#"
+            [relabel]
+            [assign.owners]
+            "compiler/" = ["compiler"]
+            [ping.compiler]
+            message = "ping!"
+            [nominate.teams]
+            compiler = "T-compiler"
+            [prioritize]
+            label = "I-prioritize"
+            [glacier]
+            [major-change]
+            zulip_ping = "T-compiler"
+            second_label = "final-comment-period"
+            meeting_label = "to-announce"
+            zulip_stream = 1
+            [shortcut]
+            [note]
+            [transfer]
+            [remind]
+            [milestone]
+            [close]
+            [reopen]
+            [lock]
+            "#,
+        );
+        // Every other piece of text between backticks is code.
+        let commands: Vec<_> = text
+            .split('`')
+            .skip(1)
+            .step_by(2)
+            .filter(|code| code.starts_with("@rustbot ") || code.starts_with("r? "))
+            .collect();
+        assert_eq!(commands.len(), 31);
+        for command in commands {
+            let example = example(command);
+            let parsed: Vec<_> = Input::new(&example, vec!["rustbot"], None).collect();
+            assert!(
+                matches!(parsed.as_slice(), [parsed] if parsed.is_ok()),
+                "`{example}` from `{command}` parsed as {parsed:?}"
+            );
+        }
+    }
+}
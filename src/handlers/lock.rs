@@ -0,0 +1,43 @@
+//! Allows to lock or unlock the conversation on an issue or a PR
+
+use crate::{
+    config::LockConfig,
+    github::{Event, LockReason},
+    handlers::{Context, UserError},
+};
+use parser::command::lock::{self, LockCommand};
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    _config: &LockConfig,
+    event: &Event,
+    cmd: LockCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    let is_team_member = event
+        .user()
+        .is_team_member(&ctx.github)
+        .await
+        .unwrap_or(false);
+    if !is_team_member {
+        let action = match cmd {
+            LockCommand::Lock { .. } => "lock",
+            LockCommand::Unlock => "unlock",
+        };
+        return Err(UserError(format!("Only team members can {action} conversations.")).into());
+    }
+    match cmd {
+        LockCommand::Lock { reason } => issue.lock(&ctx.github, reason.map(lock_reason)).await?,
+        LockCommand::Unlock => issue.unlock(&ctx.github).await?,
+    }
+    Ok(())
+}
+
+fn lock_reason(reason: lock::LockReason) -> LockReason {
+    match reason {
+        lock::LockReason::OffTopic => LockReason::OffTopic,
+        lock::LockReason::TooHeated => LockReason::TooHeated,
+        lock::LockReason::Resolved => LockReason::Resolved,
+        lock::LockReason::Spam => LockReason::Spam,
+    }
+}
@@ -1,8 +1,7 @@
 use crate::{
     config::MajorChangeConfig,
     github::{Event, Issue, IssuesAction, IssuesEvent, Label, ZulipGitHubReference},
-    handlers::Context,
-    interactions::ErrorComment,
+    handlers::{Context, UserError},
 };
 use anyhow::Context as _;
 use parser::command::second::SecondCommand;
@@ -88,15 +87,11 @@ pub(super) async fn handle_input(
         .iter()
         .any(|l| l.name == config.enabling_label)
     {
-        let cmnt = ErrorComment::new(
-            &event.issue,
-            format!(
-                "This issue is not ready for proposals; it lacks the `{}` label.",
-                config.enabling_label
-            ),
-        );
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError(format!(
+            "This issue is not ready for proposals; it lacks the `{}` label.",
+            config.enabling_label
+        ))
+        .into());
     }
     let zulip_msg = match cmd {
         Invocation::NewProposal => format!(
@@ -193,15 +188,11 @@ pub(super) async fn handle_command(
         .iter()
         .any(|l| l.name == config.enabling_label)
     {
-        let cmnt = ErrorComment::new(
-            &issue,
-            &format!(
-                "This issue cannot be seconded; it lacks the `{}` label.",
-                config.enabling_label
-            ),
-        );
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError(format!(
+            "This issue cannot be seconded; it lacks the `{}` label.",
+            config.enabling_label
+        ))
+        .into());
     }
 
     let is_team_member = event
@@ -212,9 +203,7 @@ pub(super) async fn handle_command(
         .unwrap_or(false);
 
     if !is_team_member {
-        let cmnt = ErrorComment::new(&issue, "Only team members can second issues.");
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError("Only team members can second issues.".into()).into());
     }
 
     let zulip_msg = format!(
@@ -0,0 +1,46 @@
+//! Allows team members to set or remove the milestone of an issue or a PR
+//! with `@bot milestone <title>` or `@bot milestone remove`.
+//!
+//! Unless `create-missing` is enabled, only existing milestones can be set.
+//!
+//! Parsing is done in the `parser::command::milestone` module.
+
+use crate::{
+    config::MilestoneConfig,
+    github::Event,
+    handlers::{Context, UserError},
+};
+use parser::command::milestone::MilestoneCommand;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &MilestoneConfig,
+    event: &Event,
+    cmd: MilestoneCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    let is_team_member = event
+        .user()
+        .is_team_member(&ctx.github)
+        .await
+        .unwrap_or(false);
+    if !is_team_member {
+        return Err(UserError("Only team members can change milestones.".into()).into());
+    }
+
+    match cmd {
+        MilestoneCommand::Set { title } if config.create_missing => {
+            issue.set_milestone(&ctx.github, &title).await?;
+        }
+        MilestoneCommand::Set { title } => {
+            if !issue.set_existing_milestone(&ctx.github, &title).await? {
+                return Err(UserError(format!(
+                    "There is no milestone named `{title}` in this repository."
+                ))
+                .into());
+            }
+        }
+        MilestoneCommand::Remove => issue.remove_milestone(&ctx.github).await?,
+    }
+    Ok(())
+}
@@ -3,8 +3,7 @@
 use crate::{
     config::NominateConfig,
     github::{self, Event},
-    handlers::Context,
-    interactions::ErrorComment,
+    handlers::{Context, UserError},
 };
 use parser::command::nominate::{NominateCommand, Style};
 
@@ -22,31 +21,23 @@ pub(super) async fn handle_command(
     };
 
     if !is_team_member {
-        let cmnt = ErrorComment::new(
-            &event.issue().unwrap(),
-            format!(
-                "Nominating and approving issues and pull requests is restricted to members of\
+        return Err(UserError(format!(
+            "Nominating and approving issues and pull requests is restricted to members of\
                  the Rust teams."
-            ),
-        );
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        ))
+        .into());
     }
 
     let issue_labels = event.issue().unwrap().labels();
     let mut labels_to_add = vec![];
     if cmd.style == Style::BetaApprove {
         if !issue_labels.iter().any(|l| l.name == "beta-nominated") {
-            let cmnt = ErrorComment::new(
-                &event.issue().unwrap(),
-                format!(
-                    "This pull request is not beta-nominated, so it cannot be approved yet.\
+            return Err(UserError(format!(
+                "This pull request is not beta-nominated, so it cannot be approved yet.\
                      Perhaps try to beta-nominate it by using `@{} beta-nominate <team>`?",
-                    ctx.username,
-                ),
-            );
-            cmnt.post(&ctx.github).await?;
-            return Ok(());
+                ctx.username,
+            ))
+            .into());
         }
 
         // Add the beta-accepted label, but don't attempt to remove beta-nominated or the team
@@ -56,16 +47,12 @@ pub(super) async fn handle_command(
         });
     } else {
         if !config.teams.contains_key(&cmd.team) {
-            let cmnt = ErrorComment::new(
-                &event.issue().unwrap(),
-                format!(
-                    "This team (`{}`) cannot be nominated for via this command;\
+            return Err(UserError(format!(
+                "This team (`{}`) cannot be nominated for via this command;\
                      it may need to be added to `triagebot.toml` on the default branch.",
-                    cmd.team,
-                ),
-            );
-            cmnt.post(&ctx.github).await?;
-            return Ok(());
+                cmd.team,
+            ))
+            .into());
         }
 
         let label = config.teams[&cmd.team].clone();
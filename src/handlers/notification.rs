@@ -34,7 +34,16 @@ pub async fn handle(ctx: &Context, event: &Event) -> anyhow::Result<()> {
     let short_description = match event {
         Event::Issue(e) => e.issue.title.clone(),
         Event::IssueComment(e) => format!("Comment on {}", e.issue.title),
-        Event::Push(_) | Event::Create(_) => return Ok(()),
+        Event::PullRequestReview(e) => format!("Review on {}", e.pull_request.title),
+        Event::PullRequestReviewComment(e) => format!(
+            "Review comment on {} in `{}`",
+            e.issue.title, e.comment.path
+        ),
+        Event::Push(_)
+        | Event::Create(_)
+        | Event::CheckRun(_)
+        | Event::CheckSuite(_)
+        | Event::WorkflowRun(_) => return Ok(()),
     };
 
     let mut caps = parser::get_mentions(body)
@@ -7,8 +7,7 @@
 use crate::{
     config::PingConfig,
     github::{self, Event},
-    handlers::Context,
-    interactions::ErrorComment,
+    handlers::{Context, UserError},
 };
 use parser::command::ping::PingCommand;
 
@@ -26,42 +25,29 @@ pub(super) async fn handle_command(
     };
 
     if !is_team_member {
-        let cmnt = ErrorComment::new(
-            &event.issue().unwrap(),
-            format!("Only Rust team members can ping teams."),
-        );
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError(format!("Only Rust team members can ping teams.")).into());
     }
 
     let (gh_team, config) = match config.get_by_name(&team_name.team) {
         Some(v) => v,
         None => {
-            let cmnt = ErrorComment::new(
-                &event.issue().unwrap(),
-                format!(
-                    "This team (`{}`) cannot be pinged via this command; \
+            return Err(UserError(format!(
+                "This team (`{}`) cannot be pinged via this command; \
                     it may need to be added to `triagebot.toml` on the default branch.",
-                    team_name.team,
-                ),
-            );
-            cmnt.post(&ctx.github).await?;
-            return Ok(());
+                team_name.team,
+            ))
+            .into());
         }
     };
     let team = github::get_team(&ctx.github, &gh_team).await?;
     let team = match team {
         Some(team) => team,
         None => {
-            let cmnt = ErrorComment::new(
-                &event.issue().unwrap(),
-                format!(
-                    "This team (`{}`) does not exist in the team repository.",
-                    team_name.team,
-                ),
-            );
-            cmnt.post(&ctx.github).await?;
-            return Ok(());
+            return Err(UserError(format!(
+                "This team (`{}`) does not exist in the team repository.",
+                team_name.team,
+            ))
+            .into());
         }
     };
 
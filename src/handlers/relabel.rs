@@ -3,6 +3,9 @@
 //! Labels are checked against the labels in the project; the bot does not support creating new
 //! labels.
 //!
+//! Labels can also be glob patterns: removing `S-*` removes all the matching labels on the issue,
+//! while adding `S-*` adds the one label of the repository that matches it.
+//!
 //! Parsing is done in the `parser::command::relabel` module.
 //!
 //! If the command was successful, there will be no feedback beyond the label change to reduce
@@ -11,8 +14,7 @@
 use crate::{
     config::RelabelConfig,
     github::{self, Event, GithubClient},
-    handlers::Context,
-    interactions::ErrorComment,
+    handlers::{Context, UserError},
 };
 use parser::command::relabel::{LabelDelta, RelabelCommand};
 
@@ -22,64 +24,86 @@ pub(super) async fn handle_command(
     event: &Event,
     input: RelabelCommand,
 ) -> anyhow::Result<()> {
-    let mut results = vec![];
+    let issue = event.issue().unwrap();
+    let mut to_remove = vec![];
     let mut to_add = vec![];
+    // Only fetched when a pattern is added.
+    let mut repo_labels = None;
+    let is_member = is_member(&event.user(), &ctx.github).await;
     for delta in &input.0 {
-        let name = delta.label().as_str();
-        let err = match check_filter(name, config, is_member(&event.user(), &ctx.github).await) {
-            Ok(CheckFilterResult::Allow) => None,
-            Ok(CheckFilterResult::Deny) => Some(format!(
-                "Label {} can only be set by Rust team members",
-                name
-            )),
-            Ok(CheckFilterResult::DenyUnknown) => Some(format!(
-                "Label {} can only be set by Rust team members;\
-                 we were unable to check if you are a team member.",
-                name
-            )),
-            Err(err) => Some(err),
+        let label = delta.label();
+        let names = if !label.is_pattern() {
+            Ok(vec![label.to_string()])
+        } else {
+            let pattern = label.as_str();
+            match delta {
+                LabelDelta::Remove(_) => expand_pattern(pattern, issue.labels()),
+                LabelDelta::Add(_) => {
+                    if repo_labels.is_none() {
+                        repo_labels = Some(issue.repository().labels(&ctx.github).await?);
+                    }
+                    expand_pattern(pattern, repo_labels.as_deref().unwrap()).and_then(|names| {
+                        match names.as_slice() {
+                            [_] => Ok(names),
+                            [] => Err(format!(
+                                "Label pattern `{pattern}` does not match any label in this repository"
+                            )),
+                            _ => Err(format!(
+                                "Label pattern `{pattern}` matches several labels ({}), \
+                                 please pick one",
+                                names.join(", ")
+                            )),
+                        }
+                    })
+                }
+            }
         };
-        if let Some(msg) = err {
-            let cmnt = ErrorComment::new(&event.issue().unwrap(), msg);
-            cmnt.post(&ctx.github).await?;
-            return Ok(());
-        }
-        match delta {
-            LabelDelta::Add(label) => {
-                to_add.push(github::Label {
-                    name: label.to_string(),
-                });
+        let names = match names {
+            Ok(names) => names,
+            Err(msg) => {
+                return Err(UserError(msg).into());
+            }
+        };
+        for name in names {
+            let err = match check_filter(&name, config, is_member) {
+                Ok(CheckFilterResult::Allow) => None,
+                Ok(CheckFilterResult::Deny) => Some(format!(
+                    "Label {} can only be set by Rust team members",
+                    name
+                )),
+                Ok(CheckFilterResult::DenyUnknown) => Some(format!(
+                    "Label {} can only be set by Rust team members;\
+                         we were unable to check if you are a team member.",
+                    name
+                )),
+                Err(err) => Some(err),
+            };
+            if let Some(msg) = err {
+                return Err(UserError(msg).into());
             }
-            LabelDelta::Remove(label) => {
-                results.push((
-                    label,
-                    event.issue().unwrap().remove_label(&ctx.github, &label),
-                ));
+            match delta {
+                LabelDelta::Add(_) => to_add.push(github::Label { name }),
+                LabelDelta::Remove(_) => to_remove.push(name),
             }
         }
     }
 
-    if let Err(e) = event
-        .issue()
-        .unwrap()
-        .add_labels(&ctx.github, to_add.clone())
-        .await
-    {
+    if let Err(e) = issue.add_labels(&ctx.github, to_add.clone()).await {
         tracing::error!(
             "failed to add {:?} from issue {}: {:?}",
             to_add,
-            event.issue().unwrap().global_id(),
+            issue.global_id(),
             e
         );
         return Err(e);
     }
 
-    for (label, res) in results {
-        if let Err(e) = res.await {
+    for label in to_remove {
+        if let Err(e) = issue.remove_label(&ctx.github, &label).await {
             tracing::error!(
                 "failed to remove {:?} from issue {}: {:?}",
                 label,
-                event.issue().unwrap().global_id(),
+                issue.global_id(),
                 e
             );
             return Err(e);
@@ -89,7 +113,21 @@ pub(super) async fn handle_command(
     Ok(())
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Returns the names of the `labels` matching the glob `pattern`, ignoring
+/// case like the `allow-unauthenticated` patterns.
+fn expand_pattern(pattern: &str, labels: &[github::Label]) -> Result<Vec<String>, String> {
+    let glob = glob::Pattern::new(pattern)
+        .map_err(|e| format!("Label pattern `{pattern}` is not valid: {e}"))?;
+    let mut matchopts = glob::MatchOptions::default();
+    matchopts.case_sensitive = false;
+    Ok(labels
+        .iter()
+        .filter(|label| glob.matches_with(&label.name, matchopts))
+        .map(|label| label.name.clone())
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TeamMembership {
     Member,
     Outsider,
@@ -175,9 +213,11 @@ fn match_pattern(pattern: &str, label: &str) -> anyhow::Result<MatchPatternResul
 #[cfg(test)]
 mod tests {
     use super::{
-        check_filter, match_pattern, CheckFilterResult, MatchPatternResult, TeamMembership,
+        check_filter, expand_pattern, match_pattern, CheckFilterResult, MatchPatternResult,
+        TeamMembership,
     };
     use crate::config::RelabelConfig;
+    use crate::github;
 
     #[test]
     fn test_match_pattern() -> anyhow::Result<()> {
@@ -242,4 +282,29 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_expand_pattern() {
+        let labels: Vec<_> = ["S-waiting-on-review", "s-blocked", "T-compiler"]
+            .into_iter()
+            .map(|name| github::Label {
+                name: name.to_string(),
+            })
+            .collect();
+        assert_eq!(
+            expand_pattern("S-*", &labels),
+            Ok(vec![
+                "S-waiting-on-review".to_string(),
+                "s-blocked".to_string()
+            ])
+        );
+        assert_eq!(
+            expand_pattern("T-[cl]*", &labels),
+            Ok(vec!["T-compiler".to_string()])
+        );
+        assert_eq!(expand_pattern("A-*", &labels), Ok(vec![]));
+        assert!(expand_pattern("A-[", &labels)
+            .unwrap_err()
+            .starts_with("Label pattern `A-[` is not valid"));
+    }
 }
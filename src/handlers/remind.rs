@@ -0,0 +1,347 @@
+//! Purpose: Allow anyone to be reminded about an issue or PR later with
+//! `@bot remind me in 3 days [message]` or `@bot remind @user on 2026-11-01`,
+//! and to list the pending reminders with `@bot reminders`.
+//!
+//! Reminders are scheduled as a [`ReminderJob`], which pings the user in a
+//! comment, or in a Zulip direct message if enabled. The pending reminders of
+//! an issue are cancelled when it is closed.
+//!
+//! Parsing is done in the `parser::command::remind` module.
+
+use crate::{
+    config::RemindConfig,
+    db::jobs::{delete_job, get_jobs_by_name_and_metadata, try_insert_job},
+    github::{get_id_for_username, Event, Issue, IssuesAction, IssuesEvent},
+    handlers::{Context, UserError},
+    jobs::Job,
+    zulip::{self, MessageApiRequest, Recipient},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use parser::command::remind::{RemindCommand, Target, When};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use tokio_postgres::Client as DbClient;
+use uuid::Uuid;
+
+/// The metadata of a [`ReminderJob`].
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Reminder {
+    /// The repository of the issue, as `owner/repo`.
+    repo: String,
+    issue: u64,
+    /// The user to remind.
+    user: String,
+    /// The user who set the reminder.
+    requester: String,
+    message: Option<String>,
+    /// Whether to send the reminder on Zulip, see [`RemindConfig::zulip`].
+    zulip: bool,
+}
+
+impl Reminder {
+    /// Who set the reminder, unless it was the user themselves.
+    fn from(&self) -> String {
+        if self.requester == self.user {
+            String::new()
+        } else {
+            format!(" from {}", self.requester)
+        }
+    }
+
+    fn message(&self) -> String {
+        match &self.message {
+            Some(message) => format!(": {message}"),
+            None => ".".to_string(),
+        }
+    }
+
+    fn comment(&self) -> String {
+        format!(
+            "Reminder for @{}{}{}",
+            self.user,
+            self.from(),
+            self.message()
+        )
+    }
+
+    fn zulip_message(&self, issue: &Issue) -> String {
+        format!(
+            "Reminder{} about [{}#{}]({}){}",
+            self.from(),
+            self.repo,
+            self.issue,
+            issue.html_url,
+            self.message()
+        )
+    }
+}
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    config: &RemindConfig,
+    event: &Event,
+    cmd: RemindCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    let (who, when, message) = match cmd {
+        RemindCommand::List => return list_reminders(ctx, issue).await,
+        RemindCommand::Add { who, when, message } => (who, when, message),
+    };
+
+    let requester = event.user();
+    let user = match who {
+        Target::Me => requester.login.clone(),
+        Target::User(user) => {
+            if user != requester.login
+                && !requester.is_team_member(&ctx.github).await.unwrap_or(false)
+            {
+                return Err(UserError(
+                    "Only team members can set reminders for other users.".into(),
+                )
+                .into());
+            }
+            user
+        }
+    };
+
+    let now = Utc::now();
+    let due = match when {
+        When::In(duration) => chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|duration| now.checked_add_signed(duration)),
+        When::On(date) => NaiveDate::from_ymd_opt(date.year, date.month, date.day)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc()),
+    };
+    let Some(due) = due else {
+        return Err(UserError("That is not a valid time for a reminder.".into()).into());
+    };
+    if due <= now {
+        return Err(UserError("Reminders can only be set for the future.".into()).into());
+    }
+
+    let reminder = Reminder {
+        repo: issue.repository().to_string(),
+        issue: issue.number,
+        user,
+        requester: requester.login.clone(),
+        message,
+        zulip: config.zulip,
+    };
+    let due = schedule_reminder(&*ctx.db.get().await, &reminder, due).await?;
+
+    let whom = if reminder.user == reminder.requester {
+        "you".to_string()
+    } else {
+        format!("@{}", reminder.user)
+    };
+    issue
+        .post_comment(
+            &ctx.github,
+            &format!(
+                "I will remind {whom} on {}.",
+                due.format("%Y-%m-%d at %H:%M UTC")
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Schedules `reminder` to be sent at `due`, or a little later if another
+/// reminder is due at the same time, since jobs are identified by their name
+/// and time. Returns when it will be sent.
+async fn schedule_reminder(
+    db: &DbClient,
+    reminder: &Reminder,
+    mut due: DateTime<Utc>,
+) -> anyhow::Result<DateTime<Utc>> {
+    let metadata = serde_json::to_value(reminder)?;
+    // Inserting fails rather than replaces on a conflict, so reminders set
+    // at the same time don't overwrite each other.
+    while !try_insert_job(db, ReminderJob.name(), &due, &metadata).await? {
+        due += chrono::Duration::seconds(1);
+    }
+    Ok(due)
+}
+
+/// Returns the ID, due time and contents of the pending reminders on `issue`.
+async fn pending_reminders(
+    db: &DbClient,
+    issue: &Issue,
+) -> anyhow::Result<Vec<(Uuid, DateTime<Utc>, Reminder)>> {
+    let metadata = serde_json::json!({
+        "repo": issue.repository().to_string(),
+        "issue": issue.number,
+    });
+    get_jobs_by_name_and_metadata(db, ReminderJob.name(), &metadata)
+        .await?
+        .into_iter()
+        .map(|job| -> anyhow::Result<_> {
+            Ok((
+                job.id,
+                job.scheduled_at,
+                serde_json::from_value(job.metadata)?,
+            ))
+        })
+        .collect()
+}
+
+async fn list_reminders(ctx: &Context, issue: &Issue) -> anyhow::Result<()> {
+    let reminders = pending_reminders(&*ctx.db.get().await, issue).await?;
+    let mut text = if reminders.is_empty() {
+        "There are no pending reminders on this issue.".to_string()
+    } else {
+        "Pending reminders:\n".to_string()
+    };
+    for (_, due, reminder) in reminders {
+        // Avoid pinging the users again.
+        write!(
+            text,
+            "\n- {} for `{}`, set by `{}`",
+            due.format("%Y-%m-%d %H:%M UTC"),
+            reminder.user,
+            reminder.requester
+        )
+        .unwrap();
+        if let Some(message) = &reminder.message {
+            write!(text, ": {message}").unwrap();
+        }
+    }
+    issue.post_comment(&ctx.github, &text).await?;
+    Ok(())
+}
+
+pub(super) struct RemindInput {}
+
+pub(super) async fn parse_input(
+    _ctx: &Context,
+    event: &IssuesEvent,
+    config: Option<&RemindConfig>,
+) -> Result<Option<RemindInput>, String> {
+    if config.is_none() {
+        return Ok(None);
+    }
+    match event.action {
+        IssuesAction::Closed | IssuesAction::Deleted | IssuesAction::Transferred => {
+            Ok(Some(RemindInput {}))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Cancels the pending reminders of a closed issue.
+pub(super) async fn handle_input(
+    ctx: &Context,
+    _config: &RemindConfig,
+    event: &IssuesEvent,
+    _input: RemindInput,
+) -> anyhow::Result<()> {
+    let db = ctx.db.get().await;
+    for (id, _, _) in pending_reminders(&db, &event.issue).await? {
+        delete_job(&db, &id).await?;
+    }
+    Ok(())
+}
+
+pub struct ReminderJob;
+
+#[async_trait]
+impl Job for ReminderJob {
+    fn name(&self) -> &'static str {
+        "reminder"
+    }
+
+    async fn run(&self, ctx: &Context, metadata: &serde_json::Value) -> anyhow::Result<()> {
+        let reminder: Reminder = serde_json::from_value(metadata.clone())?;
+        let issue = ctx.github.get_issue(&reminder.repo, reminder.issue).await?;
+        // Reminders are cancelled when the issue is closed, but the event may
+        // have been missed.
+        if !issue.is_open() {
+            return Ok(());
+        }
+        if reminder.zulip && send_on_zulip(ctx, &reminder, &issue).await? {
+            return Ok(());
+        }
+        issue.post_comment(&ctx.github, &reminder.comment()).await?;
+        Ok(())
+    }
+}
+
+/// Sends `reminder` as a Zulip direct message, returning `false` if the user
+/// has no known Zulip account.
+async fn send_on_zulip(ctx: &Context, reminder: &Reminder, issue: &Issue) -> anyhow::Result<bool> {
+    let Some(github_id) = get_id_for_username(&ctx.github, &reminder.user).await? else {
+        return Ok(false);
+    };
+    let Some(zulip_id) = zulip::to_zulip_id(&ctx.github, github_id).await? else {
+        return Ok(false);
+    };
+    let member = zulip::get_member(ctx.github.raw(), zulip_id).await?;
+    MessageApiRequest {
+        recipient: Recipient::Private {
+            id: member.user_id,
+            email: &member.email,
+        },
+        content: &reminder.zulip_message(issue),
+    }
+    .send(ctx.github.raw())
+    .await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::run_test;
+
+    fn reminder(requester: &str, message: Option<&str>) -> Reminder {
+        Reminder {
+            repo: "rust-lang/rust".to_string(),
+            issue: 123,
+            user: "alice".to_string(),
+            requester: requester.to_string(),
+            message: message.map(str::to_string),
+            zulip: false,
+        }
+    }
+
+    #[test]
+    fn reminder_comment() {
+        assert_eq!(reminder("alice", None).comment(), "Reminder for @alice.");
+        assert_eq!(
+            reminder("bob", Some("check the perf run")).comment(),
+            "Reminder for @alice from bob: check the perf run"
+        );
+    }
+
+    #[tokio::test]
+    async fn reminders_due_together_are_all_kept() {
+        run_test(|ctx| async {
+            let db = ctx.db_client().await;
+            let due = Utc::now() + chrono::Duration::days(1);
+
+            let (first, second) = tokio::join!(
+                schedule_reminder(&db, &reminder("alice", Some("first")), due),
+                schedule_reminder(&db, &reminder("bob", Some("second")), due),
+            );
+            let mut due_times = vec![first?, second?];
+            due_times.sort();
+            assert_eq!(due_times, [due, due + chrono::Duration::seconds(1)]);
+
+            let metadata = serde_json::json!({ "repo": "rust-lang/rust", "issue": 123 });
+            let jobs = get_jobs_by_name_and_metadata(&db, ReminderJob.name(), &metadata).await?;
+            assert_eq!(jobs.len(), 2);
+            Ok(ctx)
+        })
+        .await;
+    }
+
+    #[test]
+    fn reminder_metadata_matches_issue() {
+        // The pending reminders of an issue are found by these keys.
+        let metadata = serde_json::to_value(reminder("bob", None)).unwrap();
+        assert_eq!(metadata["repo"], "rust-lang/rust");
+        assert_eq!(metadata["issue"], 123);
+    }
+}
@@ -0,0 +1,27 @@
+//! Allows to reopen an issue or a PR
+
+use crate::{
+    config::ReopenConfig,
+    github::Event,
+    handlers::{Context, UserError},
+};
+use parser::command::reopen::ReopenCommand;
+
+pub(super) async fn handle_command(
+    ctx: &Context,
+    _config: &ReopenConfig,
+    event: &Event,
+    _cmd: ReopenCommand,
+) -> anyhow::Result<()> {
+    let issue = event.issue().unwrap();
+    let is_team_member = event
+        .user()
+        .is_team_member(&ctx.github)
+        .await
+        .unwrap_or(false);
+    if !is_team_member {
+        return Err(UserError("Only team members can reopen issues.".into()).into());
+    }
+    issue.reopen(&ctx.github).await?;
+    Ok(())
+}
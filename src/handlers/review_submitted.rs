@@ -1,4 +1,6 @@
-use crate::github::{Issue, IssueCommentAction, IssueCommentEvent, Label, PullRequestReviewState};
+use crate::github::{
+    Label, PullRequestReviewAction, PullRequestReviewEvent, PullRequestReviewState,
+};
 use crate::{config::ReviewSubmittedConfig, github::Event, handlers::Context};
 
 pub(crate) async fn handle(
@@ -6,33 +8,34 @@ pub(crate) async fn handle(
     event: &Event,
     config: &ReviewSubmittedConfig,
 ) -> anyhow::Result<()> {
-    if let Event::IssueComment(
-        event @ IssueCommentEvent {
-            action: IssueCommentAction::Created,
-            issue: Issue {
-                pull_request: Some(_),
-                ..
-            },
+    if let Event::PullRequestReview(
+        event @ PullRequestReviewEvent {
+            action: PullRequestReviewAction::Submitted,
             ..
         },
     ) = event
     {
-        if event.comment.pr_review_state != Some(PullRequestReviewState::ChangesRequested) {
-            return Ok(());
-        }
+        let new_label = match event.review.pr_review_state {
+            Some(PullRequestReviewState::ChangesRequested) => &config.reviewed_label,
+            Some(PullRequestReviewState::Approved) => match &config.approved_label {
+                Some(label) => label,
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
 
-        if event.issue.assignees.contains(&event.comment.user) {
+        if event.pull_request.assignees.contains(&event.review.user) {
             // Remove review labels
             for label in &config.review_labels {
-                event.issue.remove_label(&ctx.github, &label).await?;
+                event.pull_request.remove_label(&ctx.github, &label).await?;
             }
-            // Add waiting on author
+            // Add waiting on author, or the approved label
             event
-                .issue
+                .pull_request
                 .add_labels(
                     &ctx.github,
                     vec![Label {
-                        name: config.reviewed_label.clone(),
+                        name: new_label.clone(),
                     }],
                 )
                 .await?;
@@ -3,11 +3,11 @@
 //! Parsing is done in the `parser::command::shortcut` module.
 
 use crate::{
-    config::ShortcutConfig,
+    config::{self, ShortcutConfig},
     db::issue_data::IssueData,
     github::{Event, Label},
-    handlers::Context,
-    interactions::ErrorComment,
+    handlers::{Context, UserError},
+    messages::{self, Message},
 };
 use parser::command::shortcut::ShortcutCommand;
 
@@ -31,9 +31,7 @@ pub(super) async fn handle_command(
     // NOTE: if shortcuts available to issues are created, they need to be allowed here
     if !issue.is_pr() {
         let msg = format!("The \"{:?}\" shortcut only works on pull requests.", input);
-        let cmnt = ErrorComment::new(&issue, msg);
-        cmnt.post(&ctx.github).await?;
-        return Ok(());
+        return Err(UserError(msg).into());
     }
 
     let issue_labels = issue.labels();
@@ -72,9 +70,11 @@ pub(super) async fn handle_command(
             IssueData::load(&mut db, &issue, AUTHOR_REMINDER_KEY).await?;
 
         if state.data.reminder_comment.is_none() {
-            let comment_body = format!(
-                "Reminder, once the PR becomes ready for a review, use `@{bot} ready`.",
-                bot = &ctx.username,
+            let repo_config = config::get(&ctx.github, event.repo()).await?;
+            let comment_body = messages::render(
+                repo_config.messages.as_ref(),
+                Message::AuthorReminder,
+                serde_json::json!({ "author": issue.user.login, "bot": ctx.username }),
             );
             let comment = issue
                 .post_comment(&ctx.github, comment_body.as_str())
@@ -1,46 +1,64 @@
 //! For pull requests that have changed the triagebot.toml, validate that the
 //! changes are a valid configuration file.
 //! It won't validate anything unless the PR is open and has changed.
+//!
+//! Besides the checks of [`crate::config::check_config`], which the
+//! `check-config` binary also runs, this checks that the file makes sense for
+//! the repository: referenced labels exist, names in `assign` resolve to
+//! teams, groups or users, and `owners` paths match some file. All problems
+//! are reported together in a single review on the PR.
 
 use crate::{
-    config::{ValidateConfig, CONFIG_FILE_NAME},
-    github::IssuesAction,
+    config::{
+        config_problems, owner_matcher, parse_config, sorted, AssignConfig, Config, ValidateConfig,
+        CONFIG_FILE_NAME,
+    },
+    github::{Issue, IssueRepository, IssuesAction},
     handlers::{Context, IssuesEvent},
 };
+use rust_team_data::v1::Teams;
+use std::collections::{BTreeMap, HashSet};
 use tracing as log;
 
 pub(super) async fn parse_input(
-    ctx: &Context,
+    _ctx: &Context,
     event: &IssuesEvent,
     _config: Option<&ValidateConfig>,
 ) -> Result<Option<()>, String> {
+    // `[validate-config]` is enabled even when it is not in the
+    // configuration, so this runs on every repository.
     if !matches!(
         event.action,
         IssuesAction::Opened | IssuesAction::Reopened | IssuesAction::Synchronize
     ) {
         return Ok(None);
     }
-    // All processing needs to be done in parse_input (instead of
-    // handle_input) because we want this to *always* run. handle_input
-    // requires the config to exist in triagebot.toml, but we want this to run
-    // even if it isn't configured. As a consequence, error handling needs to
-    // be a little more cautious here, since we don't want to relay
-    // un-actionable errors to the user.
+    Ok(Some(()))
+}
+
+pub(super) async fn handle_input(
+    ctx: &Context,
+    _config: &ValidateConfig,
+    event: &IssuesEvent,
+    _input: (),
+) -> anyhow::Result<()> {
+    // Problems fetching what to validate are only logged, since they aren't
+    // actionable by the author of the PR.
     let diff = match event.issue.diff(&ctx.github).await {
         Ok(Some(diff)) => diff,
-        Ok(None) => return Ok(None),
+        Ok(None) => return Ok(()),
         Err(e) => {
             log::error!("failed to get diff {e}");
-            return Ok(None);
+            return Ok(());
         }
     };
     if !diff.iter().any(|diff| diff.path == CONFIG_FILE_NAME) {
-        return Ok(None);
+        return Ok(());
     }
 
     let Some(pr_source) = &event.issue.head else {
         log::error!("expected head commit in {event:?}");
-        return Ok(None);
+        return Ok(());
     };
     let triagebot_content = match ctx
         .github
@@ -50,75 +68,407 @@ pub(super) async fn parse_input(
         Ok(Some(c)) => c,
         Ok(None) => {
             log::error!("{CONFIG_FILE_NAME} modified, but failed to get content");
-            return Ok(None);
+            return Ok(());
         }
         Err(e) => {
             log::error!("failed to get {CONFIG_FILE_NAME}: {e}");
-            return Ok(None);
+            return Ok(());
         }
     };
 
     let triagebot_content = String::from_utf8_lossy(&*triagebot_content);
-    if let Err(e) = toml::from_str::<crate::handlers::Config>(&triagebot_content) {
-        let position = match e.span() {
-            // toml sometimes gives bad spans, see https://github.com/toml-rs/toml/issues/589
-            Some(span) if span != (0..0) => {
-                let (line, col) = translate_position(&triagebot_content, span.start);
-                let url = format!(
-                    "https://github.com/{}/blob/{}/{CONFIG_FILE_NAME}#L{line}",
-                    pr_source.repo.full_name, pr_source.sha
-                );
-                format!(" at position [{line}:{col}]({url})",)
+    let problems = match parse_config(&triagebot_content) {
+        Ok(config) => {
+            let mut problems = Vec::new();
+            let repo = event.issue.repository();
+            problems.extend(label_problems(ctx, repo, &config).await);
+            problems.extend(config_problems(&config));
+            if let Some(assign) = &config.assign {
+                problems.extend(name_problems(ctx, &repo.organization, assign).await);
+                match pr_source
+                    .repo
+                    .get_file_paths(&ctx.github, &pr_source.sha)
+                    .await
+                {
+                    Ok(Some(files)) => problems.extend(unmatched_owner_problems(assign, &files)),
+                    Ok(None) => log::warn!(
+                        "{} is too large to check `assign.owners` paths",
+                        pr_source.repo.full_name
+                    ),
+                    Err(e) => log::error!("failed to list files: {e:?}"),
+                }
             }
-            Some(_) | None => String::new(),
-        };
+            problems
+        }
+        Err(e) => {
+            let position = match e.position {
+                Some((line, col)) => {
+                    let url = format!(
+                        "https://github.com/{}/blob/{}/{CONFIG_FILE_NAME}#L{line}",
+                        pr_source.repo.full_name, pr_source.git_ref
+                    );
+                    format!(" at position [{line}:{col}]({url})",)
+                }
+                None => String::new(),
+            };
+            vec![format!(
+                "Invalid `triagebot.toml`{position}:\n\
+                `````\n\
+                {}\n\
+                `````",
+                e.error
+            )]
+        }
+    };
+    if problems.is_empty() {
+        return Ok(());
+    }
 
-        return Err(format!(
-            "Invalid `triagebot.toml`{position}:\n\
-            `````\n\
-            {e}\n\
-            `````",
-        ));
+    let body = format_problems(&problems);
+    // Pushes that don't fix the problems would otherwise repeat the review.
+    if already_reported(ctx, &event.issue, &body).await {
+        return Ok(());
+    }
+    if let Err(e) = event.issue.post_review_comment(&ctx.github, &body).await {
+        log::error!("failed to post review: {e:?}");
+        // Fall back to a regular comment so the problems aren't lost.
+        event.issue.post_comment(&ctx.github, &body).await?;
     }
-    Ok(None)
+    Ok(())
 }
 
-pub(super) async fn handle_input(
-    _ctx: &Context,
-    _config: &ValidateConfig,
-    _event: &IssuesEvent,
-    _input: (),
-) -> anyhow::Result<()> {
-    Ok(())
+/// Whether the latest review by the bot on `issue` already has `body`.
+async fn already_reported(ctx: &Context, issue: &Issue, body: &str) -> bool {
+    match issue.get_reviews(&ctx.github).await {
+        Ok(reviews) => reviews
+            .iter()
+            .rev()
+            .find(|review| review.user.login == ctx.username)
+            .is_some_and(|review| review.body == body),
+        Err(e) => {
+            log::error!("failed to get reviews of {}: {e:?}", issue.global_id());
+            false
+        }
+    }
+}
+
+fn format_problems(problems: &[String]) -> String {
+    let mut body = format!("The `{CONFIG_FILE_NAME}` in this PR has the following problems:\n");
+    for problem in problems {
+        body.push_str("\n- ");
+        // Indent continuation lines so that they stay in the list item.
+        body.push_str(&problem.replace('\n', "\n  "));
+    }
+    body
+}
+
+/// Returns the labels the configuration refers to, each with the first
+/// section that refers to it.
+///
+/// Fields which hold label patterns rather than labels are not included.
+fn referenced_labels<'a>(config: &'a Config) -> BTreeMap<&'a str, &'static str> {
+    let mut labels = BTreeMap::new();
+    let mut add = |label: &'a String, section| {
+        labels.entry(label.as_str()).or_insert(section);
+    };
+    if let Some(autolabel) = &config.autolabel {
+        for (label, cfg) in &autolabel.labels {
+            add(label, "autolabel");
+            for trigger in &cfg.trigger_labels {
+                add(trigger, "autolabel");
+            }
+        }
+    }
+    if let Some(notify_zulip) = &config.notify_zulip {
+        for label in notify_zulip.labels.keys() {
+            add(label, "notify-zulip");
+        }
+    }
+    if let Some(review_submitted) = &config.review_submitted {
+        for label in &review_submitted.review_labels {
+            add(label, "review-submitted");
+        }
+        add(&review_submitted.reviewed_label, "review-submitted");
+        if let Some(label) = &review_submitted.approved_label {
+            add(label, "review-submitted");
+        }
+    }
+    if let Some(merge_conflicts) = &config.merge_conflicts {
+        for label in merge_conflicts
+            .remove
+            .iter()
+            .chain(&merge_conflicts.add)
+            .chain(&merge_conflicts.unless)
+        {
+            add(label, "merge-conflicts");
+        }
+    }
+    if let Some(prioritize) = &config.prioritize {
+        add(&prioritize.label, "prioritize");
+    }
+    if let Some(nominate) = &config.nominate {
+        for label in nominate.teams.values() {
+            add(label, "nominate");
+        }
+    }
+    labels
+}
+
+async fn label_problems(ctx: &Context, repo: &IssueRepository, config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (label, section) in referenced_labels(config) {
+        match repo.has_label(&ctx.github, label).await {
+            Ok(true) => {}
+            Ok(false) => problems.push(format!(
+                "`[{section}]`: label `{label}` does not exist in {repo}"
+            )),
+            Err(e) => log::error!("failed to check for label {label} in {repo}: {e:?}"),
+        }
+    }
+    problems
 }
 
-/// Helper to translate a toml span to a `(line_no, col_no)` (1-based).
-fn translate_position(input: &str, index: usize) -> (usize, usize) {
-    if input.is_empty() {
-        return (0, index);
-    }
-
-    let safe_index = index.min(input.len() - 1);
-    let column_offset = index - safe_index;
-
-    let nl = input[0..safe_index]
-        .as_bytes()
-        .iter()
-        .rev()
-        .enumerate()
-        .find(|(_, b)| **b == b'\n')
-        .map(|(nl, _)| safe_index - nl - 1);
-    let line_start = match nl {
-        Some(nl) => nl + 1,
-        None => 0,
+/// What a name in `assign.owners` or `assign.adhoc_groups` refers to, resolved
+/// the same way as for `r?`.
+#[derive(Debug, PartialEq)]
+enum AssignName<'a> {
+    Group,
+    Team,
+    /// A GitHub user, who may or may not exist.
+    User(&'a str),
+    /// Looks like a team, but there is no such team.
+    UnknownTeam,
+}
+
+fn resolve_name<'a>(
+    name: &'a str,
+    organization: &str,
+    config: &AssignConfig,
+    teams: &Teams,
+) -> AssignName<'a> {
+    let name = name.strip_prefix('@').unwrap_or(name);
+    let maybe_group = name
+        .strip_prefix(organization)
+        .and_then(|name| name.strip_prefix('/'))
+        .unwrap_or(name);
+    if config.adhoc_groups.contains_key(maybe_group) {
+        return AssignName::Group;
+    }
+    let maybe_team = name.strip_prefix("rust-lang/").unwrap_or(name);
+    if teams.teams.contains_key(maybe_team) {
+        return AssignName::Team;
+    }
+    if name.contains('/') {
+        return AssignName::UnknownTeam;
+    }
+    AssignName::User(name)
+}
+
+/// Returns the names in `assign.owners` and `assign.adhoc_groups`, each with
+/// the first place it appears.
+fn assign_names(config: &AssignConfig) -> BTreeMap<&str, String> {
+    let mut names = BTreeMap::new();
+    for pattern in sorted(config.owners.keys()) {
+        for name in &config.owners[pattern] {
+            names
+                .entry(name.as_str())
+                .or_insert_with(|| format!("`[assign.owners]` entry `{pattern}`"));
+        }
+    }
+    for group in sorted(config.adhoc_groups.keys()) {
+        for name in &config.adhoc_groups[group] {
+            names
+                .entry(name.as_str())
+                .or_insert_with(|| format!("`[assign.adhoc_groups]` group `{group}`"));
+        }
+    }
+    names
+}
+
+async fn name_problems(ctx: &Context, organization: &str, config: &AssignConfig) -> Vec<String> {
+    let teams = match crate::team_data::teams(&ctx.github).await {
+        Ok(teams) => teams,
+        Err(e) => {
+            log::error!("failed to get team data: {e:?}");
+            return Vec::new();
+        }
     };
-    let line = input[0..line_start]
-        .as_bytes()
-        .iter()
-        .filter(|c| **c == b'\n')
-        .count();
-    let column = input[line_start..=safe_index].chars().count() - 1;
-    let column = column + column_offset;
-
-    (line + 1, column + 1)
+    let team_members: HashSet<String> = teams
+        .teams
+        .values()
+        .flat_map(|team| &team.members)
+        .map(|member| member.github.to_lowercase())
+        .collect();
+
+    let mut problems = Vec::new();
+    for (name, location) in assign_names(config) {
+        match resolve_name(name, organization, config, &teams) {
+            AssignName::Group | AssignName::Team => {}
+            AssignName::UnknownTeam => {
+                problems.push(format!("{location}: there is no team or group `{name}`"))
+            }
+            AssignName::User(login) => {
+                if team_members.contains(&login.to_lowercase()) {
+                    continue;
+                }
+                match ctx.github.user_exists(login).await {
+                    Ok(true) => {}
+                    Ok(false) => problems.push(format!(
+                        "{location}: `{name}` is not a team, group or GitHub user"
+                    )),
+                    Err(e) => log::error!("failed to look up user {login}: {e:?}"),
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// Returns a problem for each `assign.owners` pattern that matches none of
+/// `files`.
+fn unmatched_owner_problems(config: &AssignConfig, files: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for pattern in sorted(config.owners.keys()) {
+        // Invalid patterns are reported by `config_problems`.
+        let Ok(matcher) = owner_matcher(pattern) else {
+            continue;
+        };
+        if !files
+            .iter()
+            .any(|file| matcher.matched_path_or_any_parents(file, false).is_ignore())
+        {
+            problems.push(format!(
+                "`[assign.owners]`: `{pattern}` does not match any file in the repository"
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> Config {
+        parse_config(toml).unwrap()
+    }
+
+    fn teams(teams: &[(&str, &[&str])]) -> Teams {
+        let mut teams_config = serde_json::json!({});
+        for (name, members) in teams {
+            let members: Vec<_> = members
+                .iter()
+                .map(|member| {
+                    serde_json::json!({"name": member, "github": member, "github_id": 1, "is_lead": false})
+                })
+                .collect();
+            teams_config[name] = serde_json::json!({
+                "name": name,
+                "kind": "team",
+                "members": members,
+                "alumni": [],
+                "discord": [],
+                "roles": [],
+            });
+        }
+        serde_json::from_value(teams_config).unwrap()
+    }
+
+    #[test]
+    fn collects_labels() {
+        let config = config(
+            r#"
+            [autolabel."T-compiler"]
+            trigger_labels = ["A-diagnostics"]
+            exclude_labels = ["T-*"]
+
+            [notify-zulip."I-prioritize"]
+            zulip_stream = 1
+            topic = "issue {number}"
+
+            [review-submitted]
+            review_labels = ["S-waiting-on-review"]
+            reviewed_label = "S-waiting-on-author"
+            approved_label = "S-waiting-on-bors"
+
+            [merge-conflicts]
+            add = ["has-merge-commits"]
+
+            [prioritize]
+            label = "I-prioritize"
+
+            [nominate.teams]
+            compiler = "T-compiler"
+            "#,
+        );
+        let labels = referenced_labels(&config);
+        assert_eq!(
+            labels.into_iter().collect::<Vec<_>>(),
+            [
+                ("A-diagnostics", "autolabel"),
+                ("I-prioritize", "notify-zulip"),
+                ("S-waiting-on-author", "review-submitted"),
+                ("S-waiting-on-bors", "review-submitted"),
+                ("S-waiting-on-review", "review-submitted"),
+                ("T-compiler", "autolabel"),
+                ("has-merge-commits", "merge-conflicts"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_names() {
+        let config = config(
+            r#"
+            [assign.adhoc_groups]
+            compiler-group = ["@alice"]
+            "#,
+        );
+        let assign = config.assign.as_ref().unwrap();
+        let teams = teams(&[("compiler", &["bob"])]);
+        let resolve = |name| resolve_name(name, "rust-lang", assign, &teams);
+        assert_eq!(resolve("compiler-group"), AssignName::Group);
+        assert_eq!(resolve("rust-lang/compiler-group"), AssignName::Group);
+        assert_eq!(resolve("compiler"), AssignName::Team);
+        assert_eq!(resolve("rust-lang/compiler"), AssignName::Team);
+        assert_eq!(resolve("@alice"), AssignName::User("alice"));
+        assert_eq!(resolve("rust-lang/nope"), AssignName::UnknownTeam);
+
+        let names = assign_names(assign);
+        assert_eq!(
+            names["@alice"],
+            "`[assign.adhoc_groups]` group `compiler-group`"
+        );
+    }
+
+    #[test]
+    fn unmatched_owners() {
+        let config = config(
+            r#"
+            [assign.owners]
+            "/compiler" = ["compiler"]
+            "*.md" = ["docs"]
+            "/library/alloc" = ["libs"]
+            "#,
+        );
+        let files = vec![
+            "compiler/rustc_ast/src/lib.rs".to_string(),
+            "README.md".to_string(),
+            "library/core/src/lib.rs".to_string(),
+        ];
+        let problems = unmatched_owner_problems(config.assign.as_ref().unwrap(), &files);
+        assert_eq!(
+            problems,
+            ["`[assign.owners]`: `/library/alloc` does not match any file in the repository"]
+        );
+    }
+
+    #[test]
+    fn formats_problems() {
+        let body = format_problems(&["one".to_string(), "two\nlines".to_string()]);
+        assert_eq!(
+            body,
+            "The `triagebot.toml` in this PR has the following problems:\n\n- one\n- two\n  lines"
+        );
+    }
 }
@@ -1,4 +1,6 @@
+use crate::config::MessagesConfig;
 use crate::github::{GithubClient, Issue};
+use crate::messages::{self, Message};
 use std::fmt::Write;
 
 pub struct ErrorComment<'a> {
@@ -17,15 +19,16 @@ impl<'a> ErrorComment<'a> {
         }
     }
 
-    pub async fn post(&self, client: &GithubClient) -> anyhow::Result<()> {
-        let mut body = String::new();
-        writeln!(body, "**Error**: {}", self.message)?;
-        writeln!(body)?;
-        writeln!(
-            body,
-            "Please file an issue on GitHub at [triagebot](https://github.com/rust-lang/triagebot) if there's \
-            a problem with this bot, or reach out on [#t-infra](https://rust-lang.zulipchat.com/#narrow/stream/242791-t-infra) on Zulip."
-        )?;
+    pub async fn post(
+        &self,
+        client: &GithubClient,
+        messages: Option<&MessagesConfig>,
+    ) -> anyhow::Result<()> {
+        let body = messages::render(
+            messages,
+            Message::ErrorComment,
+            serde_json::json!({ "message": self.message }),
+        );
         self.issue.post_comment(client, &body).await?;
         Ok(())
     }
@@ -46,7 +46,9 @@ use std::str::FromStr;
 use async_trait::async_trait;
 use cron::Schedule;
 
+use crate::db::deliveries::WebhookDeliveriesCleanupJob;
 use crate::handlers::pull_requests_assignment_update::PullRequestAssignmentUpdate;
+use crate::handlers::remind::ReminderJob;
 use crate::{
     db::jobs::JobSchedule,
     handlers::{docs_update::DocsUpdateJob, rustc_commits::RustcCommitsJob, Context},
@@ -66,6 +68,8 @@ pub fn jobs() -> Vec<Box<dyn Job + Send + Sync>> {
         Box::new(DocsUpdateJob),
         Box::new(RustcCommitsJob),
         Box::new(PullRequestAssignmentUpdate),
+        Box::new(WebhookDeliveriesCleanupJob),
+        Box::new(ReminderJob),
     ]
 }
 
@@ -90,6 +94,12 @@ pub fn default_jobs() -> Vec<JobSchedule> {
             schedule: Schedule::from_str("* 0,30 * * * * *").unwrap(),
             metadata: serde_json::Value::Null,
         },
+        JobSchedule {
+            name: WebhookDeliveriesCleanupJob.name(),
+            // Once a day at 3am UTC.
+            schedule: Schedule::from_str("0 0 3 * * * *").unwrap(),
+            metadata: serde_json::Value::Null,
+        },
     ]
 }
 
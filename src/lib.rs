@@ -3,24 +3,33 @@
 use crate::github::{PullRequestDetails, UserId};
 
 use anyhow::Context;
+use chrono::Utc;
 use handlers::HandlerError;
 use interactions::ErrorComment;
 use serde::Serialize;
 use std::fmt;
+use std::time::Instant;
 use tracing as log;
 
 pub mod actions;
+pub mod admin;
 pub mod agenda;
+pub mod audit;
 mod changelogs;
 pub mod config;
 pub mod db;
 pub mod github;
+pub mod github_app;
 pub mod handlers;
 pub mod interactions;
 pub mod jobs;
+mod messages;
+pub mod metrics;
 pub mod notification_listing;
 pub mod payload;
+pub mod queue;
 pub mod rfcbot;
+pub mod shutdown;
 pub mod team;
 mod team_data;
 pub mod triage;
@@ -40,13 +49,13 @@ pub enum EventName {
     PullRequest,
     /// Pull request review activity.
     ///
-    /// This gets translated to [`github::Event::IssueComment`] when sent to a handler.
+    /// This gets translated to [`github::Event::PullRequestReview`] when sent to a handler.
     ///
     /// <https://docs.github.com/en/developers/webhooks-and-events/webhooks/webhook-events-and-payloads#pull_request_review>
     PullRequestReview,
     /// A comment on a pull request review.
     ///
-    /// This gets translated to [`github::Event::IssueComment`] when sent to a handler.
+    /// This gets translated to [`github::Event::PullRequestReviewComment`] when sent to a handler.
     ///
     /// <https://docs.github.com/en/developers/webhooks-and-events/webhooks/webhook-events-and-payloads#pull_request_review_comment>
     PullRequestReviewComment,
@@ -74,6 +83,24 @@ pub enum EventName {
     ///
     /// <https://docs.github.com/en/developers/webhooks-and-events/webhooks/webhook-events-and-payloads#create>
     Create,
+    /// Activity on a single check, such as a CI job.
+    ///
+    /// This gets translated to [`github::Event::CheckRun`] when sent to a handler.
+    ///
+    /// <https://docs.github.com/en/webhooks/webhook-events-and-payloads#check_run>
+    CheckRun,
+    /// Activity on the set of checks an app ran for a commit.
+    ///
+    /// This gets translated to [`github::Event::CheckSuite`] when sent to a handler.
+    ///
+    /// <https://docs.github.com/en/webhooks/webhook-events-and-payloads#check_suite>
+    CheckSuite,
+    /// Activity on a GitHub Actions workflow run.
+    ///
+    /// This gets translated to [`github::Event::WorkflowRun`] when sent to a handler.
+    ///
+    /// <https://docs.github.com/en/webhooks/webhook-events-and-payloads#workflow_run>
+    WorkflowRun,
     /// All other unhandled webhooks.
     Other,
 }
@@ -89,6 +116,9 @@ impl std::str::FromStr for EventName {
             "issues" => EventName::Issue,
             "push" => EventName::Push,
             "create" => EventName::Create,
+            "check_run" => EventName::CheckRun,
+            "check_suite" => EventName::CheckSuite,
+            "workflow_run" => EventName::WorkflowRun,
             _ => EventName::Other,
         })
     }
@@ -107,6 +137,9 @@ impl fmt::Display for EventName {
                 EventName::PullRequest => "pull_request",
                 EventName::Push => "push",
                 EventName::Create => "create",
+                EventName::CheckRun => "check_run",
+                EventName::CheckSuite => "check_suite",
+                EventName::WorkflowRun => "workflow_run",
                 EventName::Other => "other",
             }
         )
@@ -172,37 +205,50 @@ pub fn deserialize_payload<T: serde::de::DeserializeOwned>(v: &str) -> anyhow::R
     }
 }
 
+/// What happened to a webhook delivery passed to [`webhook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookOutcome {
+    /// The event was dispatched to the handlers.
+    Processed,
+    /// The event is not one we handle.
+    Ignored,
+    /// A delivery with the same id was already handled, so this one was
+    /// skipped.
+    AlreadyProcessed,
+}
+
+/// Processes a webhook delivery.
+///
+/// If `delivery_id` is given, the delivery is recorded in the database so
+/// that it can be replayed later, and a delivery whose id was already recorded
+/// within [`db::deliveries::DELIVERY_RETENTION_DAYS`] is not handled again.
 pub async fn webhook(
     event: EventName,
     payload: String,
+    delivery_id: Option<&str>,
     ctx: &handlers::Context,
-) -> Result<bool, WebhookError> {
+) -> Result<WebhookOutcome, WebhookError> {
+    let event_name = event.to_string();
+    let received_at = Utc::now();
+    // Act as the app installation that sent the event, if we are a GitHub App.
+    let installation_ctx;
+    let ctx = match github_app::installation_id(&payload) {
+        Some(installation_id) if ctx.github_app.is_some() => {
+            installation_ctx = ctx.for_installation(installation_id).await?;
+            &installation_ctx
+        }
+        _ => ctx,
+    };
     let event = match event {
         EventName::PullRequestReview => {
             let mut payload = deserialize_payload::<github::PullRequestReviewEvent>(&payload)
                 .context("PullRequestReview failed to deserialize")
                 .map_err(anyhow::Error::from)?;
 
-            log::info!("handling pull request review comment {:?}", payload);
+            log::info!("handling pull request review {:?}", payload);
             payload.pull_request.pull_request = Some(PullRequestDetails::new());
 
-            // Treat pull request review comments exactly like pull request
-            // comments.
-            github::Event::IssueComment(github::IssueCommentEvent {
-                action: match payload.action {
-                    github::PullRequestReviewAction::Submitted => {
-                        github::IssueCommentAction::Created
-                    }
-                    github::PullRequestReviewAction::Edited => github::IssueCommentAction::Edited,
-                    github::PullRequestReviewAction::Dismissed => {
-                        github::IssueCommentAction::Deleted
-                    }
-                },
-                changes: payload.changes,
-                issue: payload.pull_request,
-                comment: payload.review,
-                repository: payload.repository,
-            })
+            github::Event::PullRequestReview(payload)
         }
         EventName::PullRequestReviewComment => {
             let mut payload = deserialize_payload::<github::PullRequestReviewComment>(&payload)
@@ -213,15 +259,7 @@ pub async fn webhook(
 
             log::info!("handling pull request review comment {:?}", payload);
 
-            // Treat pull request review comments exactly like pull request
-            // review comments.
-            github::Event::IssueComment(github::IssueCommentEvent {
-                action: payload.action,
-                changes: payload.changes,
-                issue: payload.issue,
-                comment: payload.comment,
-                repository: payload.repository,
-            })
+            github::Event::PullRequestReviewComment(payload)
         }
         EventName::IssueComment => {
             let payload = deserialize_payload::<github::IssueCommentEvent>(&payload)
@@ -263,14 +301,63 @@ pub async fn webhook(
 
             github::Event::Create(payload)
         }
+        EventName::CheckRun => {
+            let payload = deserialize_payload::<github::CheckRunEvent>(&payload)
+                .with_context(|| format!("{:?} failed to deserialize", event))
+                .map_err(anyhow::Error::from)?;
+
+            log::info!("handling check run event {:?}", payload);
+
+            github::Event::CheckRun(payload)
+        }
+        EventName::CheckSuite => {
+            let payload = deserialize_payload::<github::CheckSuiteEvent>(&payload)
+                .with_context(|| format!("{:?} failed to deserialize", event))
+                .map_err(anyhow::Error::from)?;
+
+            log::info!("handling check suite event {:?}", payload);
+
+            github::Event::CheckSuite(payload)
+        }
+        EventName::WorkflowRun => {
+            let payload = deserialize_payload::<github::WorkflowRunEvent>(&payload)
+                .with_context(|| format!("{:?} failed to deserialize", event))
+                .map_err(anyhow::Error::from)?;
+
+            log::info!("handling workflow run event {:?}", payload);
+
+            github::Event::WorkflowRun(payload)
+        }
         // Other events need not be handled
         EventName::Other => {
-            return Ok(false);
+            return Ok(WebhookOutcome::Ignored);
         }
     };
-    let errors = handlers::handle(&ctx, &event).await;
+    if let Some(delivery_id) = delivery_id {
+        let claimed = db::deliveries::claim_delivery(
+            &*ctx.db.get().await,
+            delivery_id,
+            &event_name,
+            &payload,
+            &received_at,
+        )
+        .await?;
+        if !claimed {
+            log::info!("skipping already processed delivery {delivery_id}");
+            return Ok(WebhookOutcome::AlreadyProcessed);
+        }
+    }
+    let start = Instant::now();
+    let errors = audit::with_event(
+        event_name.clone(),
+        delivery_id.map(str::to_owned),
+        handlers::handle(&ctx, &event),
+    )
+    .await;
+    let duration = start.elapsed();
     let mut other_error = false;
     let mut message = String::new();
+    let mut recorded_errors = Vec::new();
     for err in errors {
         match err {
             HandlerError::Message(msg) => {
@@ -278,17 +365,41 @@ pub async fn webhook(
                     message.push_str("\n\n");
                 }
                 message.push_str(&msg);
+                recorded_errors.push(msg);
             }
             HandlerError::Other(err) => {
                 log::error!("handling event failed: {:?}", err);
+                recorded_errors.push(format!("{:?}", err));
                 other_error = true;
             }
         }
     }
+    if let Some(delivery_id) = delivery_id {
+        let duration_ms = duration.as_millis().try_into().unwrap_or(i32::MAX);
+        if let Err(e) = db::deliveries::finish_delivery(
+            &*ctx.db.get().await,
+            delivery_id,
+            duration_ms,
+            &recorded_errors,
+        )
+        .await
+        {
+            log::error!("failed to record webhook delivery {delivery_id}: {e:?}");
+        }
+    }
     if !message.is_empty() {
         if let Some(issue) = event.issue() {
+            // The handlers have just loaded the configuration, so this is
+            // cached unless it failed to load.
+            let config = config::get(&ctx.github, event.repo()).await.ok();
+            let messages = config.as_ref().and_then(|c| c.messages.as_ref());
             let cmnt = ErrorComment::new(issue, message);
-            cmnt.post(&ctx.github).await?;
+            audit::with_event(
+                event_name,
+                delivery_id.map(str::to_owned),
+                cmnt.post(&ctx.github, messages),
+            )
+            .await?;
         }
     }
     if other_error {
@@ -296,6 +407,6 @@ pub async fn webhook(
             "handling failed, error logged",
         )))
     } else {
-        Ok(true)
+        Ok(WebhookOutcome::Processed)
     }
 }
@@ -12,12 +12,18 @@ use tokio::{task, time};
 use tower::{Service, ServiceExt};
 use tracing as log;
 use tracing::Instrument;
+use triagebot::github_app::GithubApp;
 use triagebot::handlers::pr_tracking::ReviewerWorkqueue;
 use triagebot::handlers::pull_requests_assignment_update::load_workqueue;
 use triagebot::jobs::{
     default_jobs, JOB_PROCESSING_CADENCE_IN_SECS, JOB_SCHEDULING_CADENCE_IN_SECS,
 };
-use triagebot::{db, github, handlers::Context, notification_listing, payload, EventName};
+use triagebot::payload::SignatureAlgorithm;
+use triagebot::queue::{QueuedWebhook, WebhookQueue, WEBHOOK_QUEUE_CAPACITY, WEBHOOK_WORKERS};
+use triagebot::shutdown::{self, Shutdown};
+use triagebot::{
+    admin, audit, db, github, handlers::Context, notification_listing, payload, EventName,
+};
 
 async fn handle_agenda_request(req: String) -> anyhow::Result<String> {
     if req == "/agenda/lang/triage" {
@@ -36,6 +42,7 @@ async fn handle_agenda_request(req: String) -> anyhow::Result<String> {
 async fn serve_req(
     req: Request<Body>,
     ctx: Arc<Context>,
+    queue: Arc<WebhookQueue>,
     mut agenda: impl Service<String, Response = String, Error = tower::BoxError>,
 ) -> Result<Response<Body>, hyper::Error> {
     log::info!("request = {:?}", req);
@@ -93,6 +100,13 @@ async fn serve_req(
             .body(Body::from("Triagebot is awaiting triage."))
             .unwrap());
     }
+    if req.uri.path() == "/metrics" {
+        return Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(Body::from(triagebot::metrics::render()))
+            .unwrap());
+    }
     if req.uri.path() == "/bors-commit-list" {
         let res = db::rustc_commits::get_commits_with_artifacts(&*ctx.db.get().await).await;
         let res = match res {
@@ -130,6 +144,32 @@ async fn serve_req(
             )))
             .unwrap());
     }
+    if req.uri.path() == "/audit" {
+        let accept = req
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok());
+        let query = match audit::AuditQuery::parse(req.uri.query().unwrap_or(""), accept) {
+            Ok(query) => query,
+            Err(msg) => {
+                return Ok(Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Body::from(msg))
+                    .unwrap());
+            }
+        };
+        return Ok(match audit::render(&*ctx.db.get().await, &query).await {
+            Ok(body) => Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", query.format.content_type())
+                .body(Body::from(body))
+                .unwrap(),
+            Err(e) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(format!("{:?}", e)))
+                .unwrap(),
+        });
+    }
     if req.uri.path() == "/zulip-hook" {
         let mut c = body_stream;
         let mut payload = Vec::new();
@@ -156,6 +196,65 @@ async fn serve_req(
             .body(Body::from(triagebot::zulip::respond(&ctx, req).await))
             .unwrap());
     }
+    if req.uri.path() == "/admin/replay" {
+        if req.method != hyper::Method::POST {
+            return Ok(Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(header::ALLOW, "POST")
+                .body(Body::empty())
+                .unwrap());
+        }
+        if !admin::is_authorized(&req.headers) {
+            return Ok(Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .unwrap());
+        }
+        return match admin::replay(&ctx, req.uri.query().unwrap_or("")).await {
+            Ok(summary) => Ok(Response::new(Body::from(summary))),
+            Err(e) => Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!("{:?}", e)))
+                .unwrap()),
+        };
+    }
+    if req.uri.path() == "/admin/refresh-config" {
+        if req.method != hyper::Method::POST {
+            return Ok(Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(header::ALLOW, "POST")
+                .body(Body::empty())
+                .unwrap());
+        }
+        if !admin::is_authorized(&req.headers) {
+            return Ok(Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .unwrap());
+        }
+        return match admin::refresh_config(&ctx, req.uri.query().unwrap_or("")).await {
+            Ok(summary) => Ok(Response::new(Body::from(summary))),
+            Err(e) => Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!("{:?}", e)))
+                .unwrap()),
+        };
+    }
+    if req.uri.path() == "/admin/dry-run" {
+        if !admin::is_authorized(&req.headers) {
+            return Ok(Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .unwrap());
+        }
+        return Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                serde_json::to_string(&ctx.github.dry_run_actions()).unwrap(),
+            ))
+            .unwrap());
+    }
     if req.uri.path() != "/github-hook" {
         return Ok(Response::builder()
             .status(StatusCode::NOT_FOUND)
@@ -190,21 +289,30 @@ async fn serve_req(
             .unwrap());
     };
     log::debug!("event={}", event);
-    let signature = if let Some(sig) = req.headers.get("X-Hub-Signature") {
-        match sig.to_str().ok() {
-            Some(v) => v,
-            None => {
-                return Ok(Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Body::from("X-Hub-Signature header must be UTF-8 encoded"))
-                    .unwrap());
-            }
+    let (algorithm, signature) = match (
+        req.headers.get(SignatureAlgorithm::Sha256.header()),
+        req.headers.get(SignatureAlgorithm::Sha1.header()),
+    ) {
+        (Some(sig), _) => (SignatureAlgorithm::Sha256, sig),
+        (None, Some(sig)) if payload::sha1_allowed() => (SignatureAlgorithm::Sha1, sig),
+        (None, _) => {
+            return Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("X-Hub-Signature-256 header must be set"))
+                .unwrap());
+        }
+    };
+    let signature = match signature.to_str().ok() {
+        Some(v) => v,
+        None => {
+            return Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(format!(
+                    "{} header must be UTF-8 encoded",
+                    algorithm.header()
+                )))
+                .unwrap());
         }
-    } else {
-        return Ok(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from("X-Hub-Signature header must be set"))
-            .unwrap());
     };
     log::debug!("signature={}", signature);
 
@@ -215,7 +323,7 @@ async fn serve_req(
         payload.extend_from_slice(&chunk);
     }
 
-    if let Err(_) = payload::assert_signed(signature, &payload) {
+    if let Err(_) = payload::assert_signed(algorithm, signature, &payload) {
         return Ok(Response::builder()
             .status(StatusCode::FORBIDDEN)
             .body(Body::from("Wrong signature"))
@@ -231,17 +339,53 @@ async fn serve_req(
         }
     };
 
-    match triagebot::webhook(event, payload, &ctx).await {
-        Ok(true) => Ok(Response::new(Body::from("processed request"))),
-        Ok(false) => Ok(Response::new(Body::from("ignored request"))),
-        Err(err) => {
-            log::error!("request failed: {:?}", err);
-            Ok(Response::builder()
+    let delivery_id = req
+        .headers
+        .get("X-GitHub-Delivery")
+        .and_then(|id| id.to_str().ok());
+    log::debug!("delivery_id={:?}", delivery_id);
+
+    if let EventName::Other = event {
+        return Ok(Response::new(Body::from("ignored request")));
+    }
+
+    // Store the delivery before acknowledging it, so that it can be replayed
+    // if a shutdown cuts it off before it is handled.
+    if let Some(delivery_id) = delivery_id {
+        let stored = db::deliveries::store_delivery(
+            &*ctx.db.get().await,
+            delivery_id,
+            &event.to_string(),
+            &payload,
+            &chrono::Utc::now(),
+        )
+        .await;
+        if let Err(err) = stored {
+            log::error!("failed to store webhook {delivery_id}: {err:?}");
+            return Ok(Response::builder()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from(format!("request failed: {:?}", err)))
-                .unwrap())
+                .body(Body::from("failed to store delivery"))
+                .unwrap());
         }
     }
+
+    // Handlers can take a long time, so only queue the delivery here and
+    // reply before GitHub's delivery timeout.
+    let webhook = QueuedWebhook {
+        event,
+        payload,
+        delivery_id: delivery_id.map(String::from),
+    };
+    match queue.enqueue(webhook).await {
+        Ok(()) => Ok(Response::builder()
+            .status(StatusCode::ACCEPTED)
+            .body(Body::from("queued request"))
+            .unwrap()),
+        Err(err) => Ok(Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Body::from(err.to_string()))
+            .unwrap()),
+    }
 }
 
 async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
@@ -251,7 +395,16 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         .await
         .context("database migrations")?;
 
-    let gh = github::GithubClient::new_from_env();
+    let mut gh = github::GithubClient::new_from_env();
+    gh.set_audit_log(pool.clone());
+    if std::env::var_os("TRIAGEBOT_DRY_RUN").is_some() {
+        tracing::warn!("dry-run mode enabled, GitHub mutations will only be logged");
+        gh.set_dry_run(true);
+    }
+    let github_app = GithubApp::from_env().context("GitHub App configuration")?;
+    if github_app.is_some() {
+        tracing::info!("GitHub App configured, events will be handled as their installation");
+    }
     let oc = octocrab::OctocrabBuilder::new()
         .personal_token(github::default_token_from_env())
         .build()
@@ -284,13 +437,28 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
         github: gh,
         octocrab: oc,
         workqueue: Arc::new(RwLock::new(workqueue)),
+        github_app: github_app.map(Arc::new),
     });
 
+    let (shutdown_trigger, shutdown) = shutdown::channel();
+    let grace_period = shutdown::grace_period_from_env();
+    task::spawn(shutdown_trigger.trigger_on_signal(grace_period));
+
     // Run all jobs that have a schedule (recurring jobs)
-    if !is_scheduled_jobs_disabled() {
+    let job_runner = if !is_scheduled_jobs_disabled() {
         spawn_job_scheduler(db_url);
-        spawn_job_runner(ctx.clone());
-    }
+        Some(spawn_job_runner(ctx.clone(), shutdown.clone()))
+    } else {
+        None
+    };
+
+    let queue = Arc::new(WebhookQueue::start(
+        ctx.clone(),
+        WEBHOOK_WORKERS,
+        WEBHOOK_QUEUE_CAPACITY,
+        shutdown.clone(),
+    ));
+    let drain_queue = queue.clone();
 
     let agenda = tower::ServiceBuilder::new()
         .buffer(10)
@@ -310,6 +478,7 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
 
     let svc = hyper::service::make_service_fn(move |_conn| {
         let ctx = ctx.clone();
+        let queue = queue.clone();
         let agenda = agenda.clone();
         async move {
             Ok::<_, hyper::Error>(hyper::service::service_fn(move |req| {
@@ -318,7 +487,7 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
                 // Only log the webhook responses at INFO level to avoid flooding the
                 // logs with huge responses. Other responses are at DEBUG.
                 let log_info_response = matches!(req.uri().path(), "/github-hook" | "/zulip-hook");
-                serve_req(req, ctx.clone(), agenda.clone())
+                serve_req(req, ctx.clone(), queue.clone(), agenda.clone())
                     .map(move |mut resp| {
                         if let Ok(resp) = &mut resp {
                             resp.headers_mut()
@@ -337,9 +506,25 @@ async fn run_server(addr: SocketAddr) -> anyhow::Result<()> {
     });
     log::info!("Listening on http://{}", addr);
 
-    let serve_future = Server::bind(&addr).serve(svc);
+    let mut server_shutdown = shutdown.clone();
+    let serve_future = Server::bind(&addr)
+        .serve(svc)
+        .with_graceful_shutdown(async move {
+            server_shutdown.requested().await;
+        });
 
     serve_future.await?;
+
+    // The server has stopped accepting connections and answered the requests
+    // in flight. Now wait for the queued webhooks and the current job pass,
+    // which stop by themselves at the shutdown deadline.
+    drain_queue.drain().await;
+    if let Some(job_runner) = job_runner {
+        if let Err(err) = job_runner.await {
+            log::error!("job runner died during shutdown (error={err})");
+        }
+    }
+    log::info!("shutdown complete");
     Ok(())
 }
 
@@ -385,17 +570,24 @@ fn spawn_job_scheduler(db_url: String) {
 /// The runner wakes up every `JOB_PROCESSING_CADENCE_IN_SECS` seconds to
 /// check if any jobs have been put into the queue by the scheduler. They
 /// will get popped off the queue and run if any are found.
-fn spawn_job_runner(ctx: Arc<Context>) {
+///
+/// The returned task completes once `shutdown` has started and the current
+/// pass over the jobs is done.
+fn spawn_job_runner(ctx: Arc<Context>, shutdown: Shutdown) -> task::JoinHandle<()> {
     task::spawn(async move {
         loop {
             let ctx = ctx.clone();
+            let mut shutdown = shutdown.clone();
             let res = task::spawn(async move {
                 let mut interval =
                     time::interval(time::Duration::from_secs(JOB_PROCESSING_CADENCE_IN_SECS));
 
                 loop {
-                    interval.tick().await;
-                    db::run_scheduled_jobs(&ctx)
+                    tokio::select! {
+                        _ = interval.tick() => {}
+                        _ = shutdown.requested() => return,
+                    }
+                    db::run_scheduled_jobs(&ctx, &shutdown)
                         .await
                         .context("run database scheduled jobs")
                         .unwrap();
@@ -403,6 +595,7 @@ fn spawn_job_runner(ctx: Arc<Context>) {
             });
 
             match res.await {
+                Ok(()) => return,
                 Err(err) if err.is_panic() => {
                     /* handle panic in above task, re-launching */
                     tracing::error!("run_scheduled_jobs task died (error={err})");
@@ -411,7 +604,7 @@ fn spawn_job_runner(ctx: Arc<Context>) {
                 _ => unreachable!(),
             }
         }
-    });
+    })
 }
 
 /// Determines whether or not background scheduled jobs should be disabled for
@@ -0,0 +1,280 @@
+//! Messages the bot posts which repositories can replace with their own
+//! [Tera](https://keats.github.io/tera/docs/#templates) templates in the
+//! `[messages]` section of `triagebot.toml`. The variables each message is
+//! rendered with are documented on [`MessagesConfig`].
+
+use crate::config::MessagesConfig;
+use tracing as log;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Message {
+    NewUserWelcome,
+    ReturningUserWelcome,
+    OnVacation,
+    ReviewerHasNoCapacity,
+    NoReviewerHasCapacity,
+    AuthorReminder,
+    ErrorComment,
+}
+
+const NEW_USER_WELCOME: &str = "Thanks for the pull request, and welcome! \
+The Rust team is excited to review your changes, and you should hear from \
+{% if assignee %}@{{ assignee }} (or someone else)\
+{% else %}@Mark-Simulacrum (NB. this repo may be misconfigured){% endif %} \
+some time within the next two weeks.\
+{% if contributing_url %}
+
+Please see [the contribution \
+instructions]({{ contributing_url }}) for more information. Namely, in order to ensure the \
+minimum review times lag, PR authors and assigned reviewers should ensure that the review \
+label (`S-waiting-on-review` and `S-waiting-on-author`) stays updated, invoking these commands \
+when appropriate:
+
+- `@{{ bot }} author`: the review is finished, PR author should check the comments and take action accordingly
+- `@{{ bot }} review`: the author is ready for a review, this PR will be queued again in the reviewer's queue\
+{% endif %}";
+
+const RETURNING_USER_WELCOME: &str = "{% if assignee %}r? @{{ assignee }}
+
+{{ bot }} has assigned @{{ assignee }}.
+They will have a look at your PR within the next two weeks and either review your PR or \
+reassign to another reviewer.
+
+Use `r?` to explicitly pick a reviewer\
+{% else %}@{{ author }}: no appropriate reviewer found, use `r?` to override{% endif %}";
+
+const ON_VACATION: &str = "{{ username }} is on vacation.
+
+Please choose another assignee.";
+
+const REVIEWER_HAS_NO_CAPACITY: &str = "
+`{{ username }}` has insufficient capacity to be assigned the pull request at this time. PR assignment has been reverted.
+
+Please choose another assignee.
+
+(see [documentation](https://forge.rust-lang.org/triagebot/pr-assignment-tracking.html))";
+
+const NO_REVIEWER_HAS_CAPACITY: &str = "
+Could not find a reviewer with enough capacity to be assigned at this time. This is a problem.
+
+Please contact us on [#t-infra](https://rust-lang.zulipchat.com/#narrow/stream/242791-t-infra) on Zulip.
+
+cc: @jackh726 @apiraino";
+
+const AUTHOR_REMINDER: &str =
+    "Reminder, once the PR becomes ready for a review, use `@{{ bot }} ready`.";
+
+const ERROR_COMMENT: &str = "**Error**: {{ message }}
+
+Please file an issue on GitHub at [triagebot](https://github.com/rust-lang/triagebot) if there's \
+a problem with this bot, or reach out on [#t-infra](https://rust-lang.zulipchat.com/#narrow/stream/242791-t-infra) on Zulip.
+";
+
+impl Message {
+    const ALL: [Message; 7] = [
+        Message::NewUserWelcome,
+        Message::ReturningUserWelcome,
+        Message::OnVacation,
+        Message::ReviewerHasNoCapacity,
+        Message::NoReviewerHasCapacity,
+        Message::AuthorReminder,
+        Message::ErrorComment,
+    ];
+
+    /// The key of the message in `[messages]`.
+    fn name(self) -> &'static str {
+        match self {
+            Message::NewUserWelcome => "new-user-welcome",
+            Message::ReturningUserWelcome => "returning-user-welcome",
+            Message::OnVacation => "on-vacation",
+            Message::ReviewerHasNoCapacity => "reviewer-has-no-capacity",
+            Message::NoReviewerHasCapacity => "no-reviewer-has-capacity",
+            Message::AuthorReminder => "author-reminder",
+            Message::ErrorComment => "error-comment",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Message::NewUserWelcome => NEW_USER_WELCOME,
+            Message::ReturningUserWelcome => RETURNING_USER_WELCOME,
+            Message::OnVacation => ON_VACATION,
+            Message::ReviewerHasNoCapacity => REVIEWER_HAS_NO_CAPACITY,
+            Message::NoReviewerHasCapacity => NO_REVIEWER_HAS_CAPACITY,
+            Message::AuthorReminder => AUTHOR_REMINDER,
+            Message::ErrorComment => ERROR_COMMENT,
+        }
+    }
+
+    fn configured(self, config: &MessagesConfig) -> Option<&str> {
+        match self {
+            Message::NewUserWelcome => config.new_user_welcome.as_deref(),
+            Message::ReturningUserWelcome => config.returning_user_welcome.as_deref(),
+            Message::OnVacation => config.on_vacation.as_deref(),
+            Message::ReviewerHasNoCapacity => config.reviewer_has_no_capacity.as_deref(),
+            Message::NoReviewerHasCapacity => config.no_reviewer_has_capacity.as_deref(),
+            Message::AuthorReminder => config.author_reminder.as_deref(),
+            Message::ErrorComment => config.error_comment.as_deref(),
+        }
+    }
+}
+
+/// Renders `message` with the repository's template from `config`, if it
+/// configured one. `vars` is a JSON object of the template variables.
+pub(crate) fn render(
+    config: Option<&MessagesConfig>,
+    message: Message,
+    vars: serde_json::Value,
+) -> String {
+    let custom = config.and_then(|messages| message.configured(messages));
+    render_with(message, custom, vars)
+}
+
+/// Renders the built-in text of `message`.
+pub(crate) fn render_default(message: Message, vars: serde_json::Value) -> String {
+    render_with(message, None, vars)
+}
+
+fn render_with(message: Message, custom: Option<&str>, vars: serde_json::Value) -> String {
+    let context = tera::Context::from_serialize(vars).expect("message variables are an object");
+    if let Some(template) = custom {
+        match tera::Tera::one_off(template, &context, false) {
+            Ok(text) => return text,
+            Err(e) => log::error!(
+                "failed to render the configured `{}` message, using the default: {e:?}",
+                message.name()
+            ),
+        }
+    }
+    tera::Tera::one_off(message.default_template(), &context, false)
+        .expect("built-in messages are valid templates")
+}
+
+/// Returns the configured templates which don't parse, as `(name, error)`.
+pub(crate) fn template_errors(config: &MessagesConfig) -> Vec<(&'static str, String)> {
+    Message::ALL
+        .iter()
+        .filter_map(|&message| {
+            let template = message.configured(config)?;
+            let mut tera = tera::Tera::default();
+            let e = tera.add_raw_template(message.name(), template).err()?;
+            // The cause has the details of the syntax error.
+            let cause = std::error::Error::source(&e).map_or(e.to_string(), |s| s.to_string());
+            Some((message.name(), cause))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn messages(toml: &str) -> MessagesConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn defaults_are_unchanged() {
+        assert_eq!(
+            render_default(
+                Message::NewUserWelcome,
+                json!({"author": "a", "bot": "rustbot", "assignee": "b", "contributing_url": null})
+            ),
+            "Thanks for the pull request, and welcome! The Rust team is excited to review your \
+             changes, and you should hear from @b (or someone else) some time within the next \
+             two weeks."
+        );
+        let with_contributing = render_default(
+            Message::NewUserWelcome,
+            json!({"author": "a", "bot": "rustbot", "assignee": null, "contributing_url": "https://x"}),
+        );
+        assert!(with_contributing.contains(
+            "hear from @Mark-Simulacrum (NB. this repo may be misconfigured) some time within \
+             the next two weeks.\n\nPlease see [the contribution instructions](https://x) for"
+        ));
+        assert!(with_contributing.ends_with(
+            "queue\n- `@rustbot review`: the author is ready for a review, this PR will be \
+             queued again in the reviewer's queue"
+        ));
+
+        assert_eq!(
+            render_default(
+                Message::ReturningUserWelcome,
+                json!({"author": "a", "bot": "rustbot", "assignee": "b"})
+            ),
+            "r? @b\n\nrustbot has assigned @b.\nThey will have a look at your PR within the next \
+             two weeks and either review your PR or reassign to another reviewer.\n\n\
+             Use `r?` to explicitly pick a reviewer"
+        );
+        assert_eq!(
+            render_default(
+                Message::ReturningUserWelcome,
+                json!({"author": "a", "bot": "rustbot", "assignee": null})
+            ),
+            "@a: no appropriate reviewer found, use `r?` to override"
+        );
+        assert_eq!(
+            render_default(Message::OnVacation, json!({"username": "b"})),
+            "b is on vacation.\n\nPlease choose another assignee."
+        );
+        assert!(
+            render_default(Message::ReviewerHasNoCapacity, json!({"username": "b"}))
+                .starts_with("\n`b` has insufficient capacity")
+        );
+        assert!(render_default(Message::NoReviewerHasCapacity, json!({}))
+            .ends_with("on Zulip.\n\ncc: @jackh726 @apiraino"));
+        assert_eq!(
+            render_default(Message::AuthorReminder, json!({"bot": "rustbot"})),
+            "Reminder, once the PR becomes ready for a review, use `@rustbot ready`."
+        );
+        assert_eq!(
+            render_default(Message::ErrorComment, json!({"message": "oops {{ x }}"})),
+            "**Error**: oops {{ x }}\n\nPlease file an issue on GitHub at \
+             [triagebot](https://github.com/rust-lang/triagebot) if there's a problem with this \
+             bot, or reach out on [#t-infra](https://rust-lang.zulipchat.com/#narrow/stream/242791-t-infra) \
+             on Zulip.\n"
+        );
+    }
+
+    #[test]
+    fn configured_template() {
+        let config = messages(
+            r#"
+            error-comment = "Oh no: {{ message }}"
+            author-reminder = "{{ bot | upper }} says hi to {{ nobody }}"
+            "#,
+        );
+        assert_eq!(
+            render_with(
+                Message::ErrorComment,
+                Message::ErrorComment.configured(&config),
+                json!({"message": "bad"})
+            ),
+            "Oh no: bad"
+        );
+        // Templates that fail to render fall back to the default.
+        assert_eq!(
+            render_with(
+                Message::AuthorReminder,
+                Message::AuthorReminder.configured(&config),
+                json!({"author": "a", "bot": "rustbot"})
+            ),
+            "Reminder, once the PR becomes ready for a review, use `@rustbot ready`."
+        );
+        assert_eq!(Message::OnVacation.configured(&config), None);
+    }
+
+    #[test]
+    fn reports_invalid_templates() {
+        let config = messages(
+            r#"
+            on-vacation = "{{ username }} is away"
+            error-comment = "{% if message %}unclosed"
+            "#,
+        );
+        let errors = template_errors(&config);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "error-comment");
+    }
+}
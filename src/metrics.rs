@@ -0,0 +1,149 @@
+//! Prometheus metrics, served in the text format at `/metrics`.
+
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Encoder, Histogram, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
+    TextEncoder,
+};
+use std::future::Future;
+use std::sync::LazyLock;
+
+static HANDLER_INVOCATIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "triagebot_handler_invocations_total",
+        "Number of times a handler was run",
+        &["handler"]
+    )
+    .unwrap()
+});
+
+static HANDLER_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "triagebot_handler_errors_total",
+        "Number of times a handler returned an error",
+        &["handler"]
+    )
+    .unwrap()
+});
+
+static HANDLER_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "triagebot_handler_duration_seconds",
+        "Time spent running a handler",
+        &["handler"]
+    )
+    .unwrap()
+});
+
+static JOB_RUNS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "triagebot_job_runs_total",
+        "Number of scheduled job runs, by outcome",
+        &["job", "outcome"]
+    )
+    .unwrap()
+});
+
+static DB_POOL_WAIT: LazyLock<Histogram> = LazyLock::new(|| {
+    register_histogram!(
+        "triagebot_db_pool_wait_seconds",
+        "Time spent waiting for a database connection from the pool",
+        vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0]
+    )
+    .unwrap()
+});
+
+static GITHUB_RATE_LIMIT_REMAINING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "triagebot_github_rate_limit_remaining",
+        "Remaining GitHub API requests in the current rate limit window, as of the last response",
+        &["resource"]
+    )
+    .unwrap()
+});
+
+static WEBHOOK_QUEUE_DEPTH: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "triagebot_webhook_queue_depth",
+        "Number of webhook deliveries waiting to be handled"
+    )
+    .unwrap()
+});
+
+/// Runs a handler, recording its invocation, latency and whether it failed.
+///
+/// The GitHub actions it performs are attributed to it in the audit log.
+pub async fn track_handler<T>(
+    handler: &str,
+    fut: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    HANDLER_INVOCATIONS.with_label_values(&[handler]).inc();
+    let timer = HANDLER_DURATION.with_label_values(&[handler]).start_timer();
+    let result = crate::audit::with_handler(handler, fut).await;
+    timer.observe_duration();
+    if result.is_err() {
+        HANDLER_ERRORS.with_label_values(&[handler]).inc();
+    }
+    result
+}
+
+/// Records the outcome of a scheduled job run.
+pub fn record_job_run(job: &str, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    JOB_RUNS.with_label_values(&[job, outcome]).inc();
+}
+
+/// Records how long it took to get a connection from the database pool.
+pub fn record_db_pool_wait(wait: std::time::Duration) {
+    DB_POOL_WAIT.observe(wait.as_secs_f64());
+}
+
+/// Records the `X-RateLimit-Remaining` value of a GitHub API response.
+pub fn record_github_rate_limit(resource: &str, remaining: i64) {
+    GITHUB_RATE_LIMIT_REMAINING
+        .with_label_values(&[resource])
+        .set(remaining);
+}
+
+/// Records that a webhook delivery was added to the queue.
+pub fn record_webhook_enqueued() {
+    WEBHOOK_QUEUE_DEPTH.inc();
+}
+
+/// Records that a webhook delivery was taken off the queue.
+pub fn record_webhook_dequeued() {
+    WEBHOOK_QUEUE_DEPTH.dec();
+}
+
+/// Renders all metrics in the Prometheus text format.
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn handler_metrics() {
+        track_handler("metrics_test_ok", async { Ok(()) })
+            .await
+            .unwrap();
+        track_handler::<()>("metrics_test_err", async { anyhow::bail!("boom") })
+            .await
+            .unwrap_err();
+
+        let output = render();
+        assert!(
+            output.contains(r#"triagebot_handler_invocations_total{handler="metrics_test_ok"} 1"#)
+        );
+        assert!(!output.contains(r#"triagebot_handler_errors_total{handler="metrics_test_ok"}"#));
+        assert!(output.contains(r#"triagebot_handler_errors_total{handler="metrics_test_err"} 1"#));
+        assert!(output
+            .contains(r#"triagebot_handler_duration_seconds_count{handler="metrics_test_ok"} 1"#));
+    }
+}
@@ -12,9 +12,82 @@ impl fmt::Display for SignedPayloadError {
 
 impl std::error::Error for SignedPayloadError {}
 
-pub fn assert_signed(signature: &str, payload: &[u8]) -> Result<(), SignedPayloadError> {
-    let signature = signature.get("sha1=".len()..).ok_or(SignedPayloadError)?;
-    let signature = match hex::decode(&signature) {
+/// The HMAC algorithm GitHub used to sign a webhook delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    /// Sent in the `X-Hub-Signature-256` header.
+    Sha256,
+    /// Sent in the legacy `X-Hub-Signature` header.
+    Sha1,
+}
+
+impl SignatureAlgorithm {
+    /// The name of the header carrying a signature of this kind.
+    pub fn header(self) -> &'static str {
+        match self {
+            SignatureAlgorithm::Sha256 => "X-Hub-Signature-256",
+            SignatureAlgorithm::Sha1 => "X-Hub-Signature",
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            SignatureAlgorithm::Sha256 => "sha256=",
+            SignatureAlgorithm::Sha1 => "sha1=",
+        }
+    }
+
+    fn digest(self) -> MessageDigest {
+        match self {
+            SignatureAlgorithm::Sha256 => MessageDigest::sha256(),
+            SignatureAlgorithm::Sha1 => MessageDigest::sha1(),
+        }
+    }
+}
+
+/// Whether deliveries signed only with the legacy SHA-1 `X-Hub-Signature`
+/// header are accepted.
+///
+/// This is off unless `GITHUB_WEBHOOK_ALLOW_SHA1` is set.
+pub fn sha1_allowed() -> bool {
+    std::env::var_os("GITHUB_WEBHOOK_ALLOW_SHA1").is_some()
+}
+
+/// Returns the currently active webhook secrets.
+///
+/// `GITHUB_WEBHOOK_SECRET` may hold several comma-separated secrets. During a
+/// rotation, set it to `new,old` until GitHub has been switched over to the
+/// new secret, then drop the old one.
+fn webhook_secrets() -> Vec<String> {
+    let secrets = std::env::var("GITHUB_WEBHOOK_SECRET").expect("Missing GITHUB_WEBHOOK_SECRET");
+    secrets
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub fn assert_signed(
+    algorithm: SignatureAlgorithm,
+    signature: &str,
+    payload: &[u8],
+) -> Result<(), SignedPayloadError> {
+    verify_signature(algorithm, signature, payload, &webhook_secrets())
+}
+
+/// Checks `signature` against the HMAC of `payload` for each of `secrets`,
+/// succeeding if any of them match.
+fn verify_signature<S: AsRef<str>>(
+    algorithm: SignatureAlgorithm,
+    signature: &str,
+    payload: &[u8],
+    secrets: &[S],
+) -> Result<(), SignedPayloadError> {
+    let signature = signature
+        .strip_prefix(algorithm.prefix())
+        .ok_or(SignedPayloadError)?;
+    let signature = match hex::decode(signature) {
         Ok(e) => e,
         Err(e) => {
             tracing::trace!("hex decode failed for {:?}: {:?}", signature, e);
@@ -22,18 +95,90 @@ pub fn assert_signed(signature: &str, payload: &[u8]) -> Result<(), SignedPayloa
         }
     };
 
-    let key = PKey::hmac(
-        std::env::var("GITHUB_WEBHOOK_SECRET")
-            .expect("Missing GITHUB_WEBHOOK_SECRET")
-            .as_bytes(),
-    )
-    .unwrap();
-    let mut signer = Signer::new(MessageDigest::sha1(), &key).unwrap();
-    signer.update(&payload).unwrap();
-    let hmac = signer.sign_to_vec().unwrap();
+    for secret in secrets {
+        let key = PKey::hmac(secret.as_ref().as_bytes()).unwrap();
+        let mut signer = Signer::new(algorithm.digest(), &key).unwrap();
+        signer.update(payload).unwrap();
+        let hmac = signer.sign_to_vec().unwrap();
+
+        if hmac.len() == signature.len() && memcmp::eq(&hmac, &signature) {
+            return Ok(());
+        }
+    }
+    Err(SignedPayloadError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example values from GitHub's "Validating webhook deliveries" documentation.
+    const SECRET: &str = "It's a Secret to Everybody";
+    const PAYLOAD: &[u8] = b"Hello, World!";
+    const SHA256: &str = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+    const SHA1: &str = "sha1=01dc10d0c83e72ed246219cdd91669667fe2ca59";
+
+    #[test]
+    fn sha256_valid() {
+        assert!(verify_signature(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &[SECRET]).is_ok());
+    }
+
+    #[test]
+    fn sha256_invalid() {
+        assert!(verify_signature(
+            SignatureAlgorithm::Sha256,
+            SHA256,
+            b"Hello, World?",
+            &[SECRET]
+        )
+        .is_err());
+        assert!(verify_signature(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &["wrong"]).is_err());
+        assert!(
+            verify_signature(SignatureAlgorithm::Sha256, "sha256=zz", PAYLOAD, &[SECRET]).is_err()
+        );
+        assert!(verify_signature(SignatureAlgorithm::Sha256, "", PAYLOAD, &[SECRET]).is_err());
+        // A SHA-1 signature must not be accepted as SHA-256, even if it is valid.
+        assert!(verify_signature(SignatureAlgorithm::Sha256, SHA1, PAYLOAD, &[SECRET]).is_err());
+    }
+
+    #[test]
+    fn sha1_valid() {
+        assert!(verify_signature(SignatureAlgorithm::Sha1, SHA1, PAYLOAD, &[SECRET]).is_ok());
+    }
+
+    #[test]
+    fn sha1_invalid() {
+        assert!(
+            verify_signature(SignatureAlgorithm::Sha1, SHA1, b"Hello, World?", &[SECRET]).is_err()
+        );
+        assert!(verify_signature(SignatureAlgorithm::Sha1, SHA1, PAYLOAD, &["wrong"]).is_err());
+        assert!(verify_signature(SignatureAlgorithm::Sha1, SHA256, PAYLOAD, &[SECRET]).is_err());
+    }
 
-    if !memcmp::eq(&hmac, &signature) {
-        return Err(SignedPayloadError);
+    #[test]
+    fn rotation() {
+        let new_sig = "sha256=693585aa43518a80e267fbc0ace9296f035480e1a1b11cd09cf6a9b8a7ef0330";
+        // Before rotation, only the old secret is configured.
+        assert!(verify_signature(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &[SECRET]).is_ok());
+        assert!(verify_signature(SignatureAlgorithm::Sha256, new_sig, PAYLOAD, &[SECRET]).is_err());
+        // During the overlap window, both secrets are accepted.
+        let both = ["new-secret", SECRET];
+        assert!(verify_signature(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &both).is_ok());
+        assert!(verify_signature(SignatureAlgorithm::Sha256, new_sig, PAYLOAD, &both).is_ok());
+        // After rotation, the old secret is no longer accepted.
+        assert!(
+            verify_signature(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &["new-secret"]).is_err()
+        );
+        assert!(verify_signature(
+            SignatureAlgorithm::Sha256,
+            new_sig,
+            PAYLOAD,
+            &["new-secret"]
+        )
+        .is_ok());
+        // No secrets configured never validates.
+        assert!(
+            verify_signature::<&str>(SignatureAlgorithm::Sha256, SHA256, PAYLOAD, &[]).is_err()
+        );
     }
-    Ok(())
 }
@@ -0,0 +1,303 @@
+//! Queue of verified webhook deliveries waiting to be handled.
+//!
+//! The webhook endpoint only verifies and enqueues deliveries, so that GitHub
+//! gets a response well before its delivery timeout even when handlers are
+//! slow. All deliveries for the same issue or PR are handled one at a time, in
+//! the order they were received, by a task that exists while that issue has
+//! deliveries waiting. Deliveries for different issues are handled
+//! concurrently, up to a limit, so a slow delivery only holds up later
+//! deliveries for its own issue.
+//!
+//! On shutdown the queue stops accepting deliveries and what is already queued
+//! is handled until the shutdown deadline. Deliveries are stored before they
+//! are queued, so those that are cut off can be replayed with
+//! `/admin/replay`.
+
+use crate::handlers::Context;
+use crate::shutdown::Shutdown;
+use crate::{metrics, EventName, WebhookOutcome};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use tracing as log;
+
+/// How many deliveries are handled concurrently.
+pub const WEBHOOK_WORKERS: usize = 8;
+
+/// How many deliveries can be waiting before the webhook endpoint starts
+/// rejecting new ones.
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 512;
+
+/// How long the webhook endpoint waits for room in a full queue before giving
+/// up.
+const ENQUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub struct QueuedWebhook {
+    pub event: EventName,
+    pub payload: String,
+    pub delivery_id: Option<String>,
+}
+
+/// Returned by [`WebhookQueue::enqueue`] when a delivery could not be queued
+/// because the queue is backed up or shutting down.
+#[derive(Debug)]
+pub struct QueueFull;
+
+impl std::fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "webhook queue is full")
+    }
+}
+
+impl std::error::Error for QueueFull {}
+
+/// A queued delivery, holding its place in the queue until it is handled.
+struct Waiting {
+    webhook: QueuedWebhook,
+    place: OwnedSemaphorePermit,
+}
+
+/// The deliveries waiting for each ordering key that has any.
+type Keys = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Waiting>>>>;
+
+pub struct WebhookQueue {
+    ctx: Arc<Context>,
+    shutdown: Shutdown,
+    keys: Keys,
+    /// Limits how many deliveries are handled at once.
+    workers: Arc<Semaphore>,
+    /// Limits how many deliveries can be waiting.
+    capacity: Arc<Semaphore>,
+    /// Cloned into every task handling deliveries, so that [`Self::drain`]
+    /// can wait for all of them by waiting for `finished` to close.
+    running: Mutex<Option<mpsc::Sender<()>>>,
+    finished: tokio::sync::Mutex<mpsc::Receiver<()>>,
+}
+
+impl WebhookQueue {
+    /// Creates a queue handling up to `workers` deliveries at once with up to
+    /// `capacity` more waiting, which stops accepting deliveries once
+    /// `shutdown` is requested.
+    pub fn start(
+        ctx: Arc<Context>,
+        workers: usize,
+        capacity: usize,
+        shutdown: Shutdown,
+    ) -> WebhookQueue {
+        let (running, finished) = mpsc::channel(1);
+        WebhookQueue {
+            ctx,
+            shutdown,
+            keys: Default::default(),
+            workers: Arc::new(Semaphore::new(workers)),
+            capacity: Arc::new(Semaphore::new(capacity)),
+            running: Mutex::new(Some(running)),
+            finished: tokio::sync::Mutex::new(finished),
+        }
+    }
+
+    /// Waits for the queued deliveries to be handled, or to be dropped at the
+    /// shutdown deadline.
+    pub async fn drain(&self) {
+        self.running.lock().unwrap().take();
+        // Nothing is ever sent, this returns once every task has exited.
+        let _ = self.finished.lock().await.recv().await;
+    }
+
+    /// Queues a delivery to be handled after all previously queued deliveries
+    /// for the same issue or PR.
+    ///
+    /// If the queue is full, this waits a little for it to catch up before
+    /// failing with [`QueueFull`].
+    pub async fn enqueue(&self, webhook: QueuedWebhook) -> Result<(), QueueFull> {
+        let key = ordering_key(&webhook.payload);
+        let running = self.running.lock().unwrap().clone();
+        let Some(running) = running.filter(|_| !self.shutdown.is_requested()) else {
+            log::error!("failed to queue webhook for {key}: shutting down");
+            return Err(QueueFull);
+        };
+        let place = tokio::time::timeout(ENQUEUE_TIMEOUT, self.capacity.clone().acquire_owned());
+        let Ok(Ok(place)) = place.await else {
+            log::error!("failed to queue webhook for {key}: queue is full");
+            return Err(QueueFull);
+        };
+        metrics::record_webhook_enqueued();
+        let waiting = Waiting { webhook, place };
+
+        // The task for a key only exits with the map locked, after checking
+        // that nothing is waiting, so a delivery sent here is never lost.
+        let mut keys = self.keys.lock().unwrap();
+        let waiting = match keys.get(&key) {
+            Some(tx) => match tx.send(waiting) {
+                Ok(()) => return Ok(()),
+                // The task died, start another one.
+                Err(mpsc::error::SendError(waiting)) => waiting,
+            },
+            None => waiting,
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = tx.send(waiting);
+        keys.insert(key.clone(), tx);
+        drop(keys);
+
+        tokio::task::spawn(run_key(
+            self.ctx.clone(),
+            key,
+            rx,
+            self.keys.clone(),
+            self.workers.clone(),
+            self.shutdown.clone(),
+            running,
+        ));
+        Ok(())
+    }
+}
+
+/// Handles the deliveries waiting for `key` in order, until there are none
+/// left or the shutdown deadline has passed.
+async fn run_key(
+    ctx: Arc<Context>,
+    key: String,
+    mut rx: mpsc::UnboundedReceiver<Waiting>,
+    keys: Keys,
+    workers: Arc<Semaphore>,
+    mut shutdown: Shutdown,
+    _running: mpsc::Sender<()>,
+) {
+    loop {
+        let waiting = {
+            let mut keys = keys.lock().unwrap();
+            let waiting = rx.try_recv().ok();
+            if waiting.is_none() {
+                keys.remove(&key);
+            }
+            waiting
+        };
+        let Some(Waiting { webhook, place }) = waiting else {
+            return;
+        };
+        let worker = tokio::select! {
+            worker = workers.clone().acquire_owned() => worker.expect("never closed"),
+            _ = shutdown.deadline() => {
+                dropped(&webhook);
+                break;
+            }
+        };
+        metrics::record_webhook_dequeued();
+        drop(place);
+
+        let delivery_id = webhook.delivery_id.clone();
+        let ctx = ctx.clone();
+        // Handle each delivery in its own task so that a panicking handler
+        // doesn't take the others for this issue down with it.
+        let mut task = tokio::task::spawn(async move {
+            let _worker = worker;
+            let QueuedWebhook {
+                event,
+                payload,
+                delivery_id,
+            } = webhook;
+            match crate::webhook(event, payload, delivery_id.as_deref(), &ctx).await {
+                Ok(WebhookOutcome::Processed) => log::info!("processed {delivery_id:?}"),
+                Ok(WebhookOutcome::Ignored) => log::info!("ignored {delivery_id:?}"),
+                Ok(WebhookOutcome::AlreadyProcessed) => {
+                    log::info!("already processed {delivery_id:?}")
+                }
+                Err(err) => log::error!("webhook {delivery_id:?} failed: {err:?}"),
+            }
+        });
+        let res = tokio::select! {
+            res = &mut task => res,
+            _ = shutdown.deadline() => {
+                task.abort();
+                log::warn!(
+                    "shutdown deadline interrupted webhook {delivery_id:?}{}",
+                    replay_hint(delivery_id.as_deref())
+                );
+                break;
+            }
+        };
+        if let Err(err) = res {
+            log::error!("webhook task died (error={err})");
+        }
+    }
+    // Anything left over was cut off by the shutdown deadline.
+    keys.lock().unwrap().remove(&key);
+    rx.close();
+    while let Ok(waiting) = rx.try_recv() {
+        dropped(&waiting.webhook);
+    }
+}
+
+fn dropped(webhook: &QueuedWebhook) {
+    metrics::record_webhook_dequeued();
+    log::warn!(
+        "shutdown deadline dropped queued webhook {:?}{}",
+        webhook.delivery_id,
+        replay_hint(webhook.delivery_id.as_deref())
+    );
+}
+
+fn replay_hint(delivery_id: Option<&str>) -> String {
+    match delivery_id {
+        Some(id) => format!(", it can be handled with /admin/replay?delivery={id}"),
+        // Only deliveries with an id are stored.
+        None => ", it cannot be replayed".to_string(),
+    }
+}
+
+/// Returns the key deliveries must be ordered by: the issue or PR they are
+/// about, or just the repository for events such as pushes.
+fn ordering_key(payload: &str) -> String {
+    let payload: serde_json::Value = serde_json::from_str(payload).unwrap_or_default();
+    let repo = payload["repository"]["full_name"]
+        .as_str()
+        .unwrap_or_default();
+    let number = payload["issue"]["number"]
+        .as_u64()
+        .or_else(|| payload["pull_request"]["number"].as_u64())
+        .or_else(|| {
+            // CI events list the PRs they ran for.
+            ["check_run", "check_suite", "workflow_run"]
+                .iter()
+                .find_map(|kind| payload[kind]["pull_requests"][0]["number"].as_u64())
+        });
+    match number {
+        Some(number) => format!("{repo}#{number}"),
+        None => repo.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordering_key_issue() {
+        let payload = r#"{"issue": {"number": 12}, "repository": {"full_name": "rust-lang/rust"}}"#;
+        assert_eq!(ordering_key(payload), "rust-lang/rust#12");
+    }
+
+    #[test]
+    fn ordering_key_pull_request() {
+        let payload =
+            r#"{"pull_request": {"number": 34}, "repository": {"full_name": "rust-lang/rust"}}"#;
+        assert_eq!(ordering_key(payload), "rust-lang/rust#34");
+    }
+
+    #[test]
+    fn ordering_key_ci() {
+        let payload = r#"{"check_suite": {"pull_requests": [{"number": 56}]}, "repository": {"full_name": "rust-lang/rust"}}"#;
+        assert_eq!(ordering_key(payload), "rust-lang/rust#56");
+    }
+
+    #[test]
+    fn ordering_key_repository() {
+        let payload =
+            r#"{"ref": "refs/heads/master", "repository": {"full_name": "rust-lang/rust"}}"#;
+        assert_eq!(ordering_key(payload), "rust-lang/rust");
+        assert_eq!(ordering_key("not json"), "");
+    }
+}
@@ -0,0 +1,132 @@
+//! Coordinates graceful shutdown.
+//!
+//! When the process is asked to stop (SIGTERM on deploy, or Ctrl-C), the
+//! server stops accepting connections and background work is given until a
+//! deadline to finish: queued webhooks are still handled and the job runner
+//! finishes its current pass. Work still running at the deadline is abandoned,
+//! recording what was interrupted so it can be retried.
+
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing as log;
+
+/// How long in-flight work gets to finish by default, chosen to fit within
+/// the usual 30 second grace period before the process is killed.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(25);
+
+/// Returns the grace period from `TRIAGEBOT_SHUTDOWN_GRACE_SECS`, or
+/// [`DEFAULT_GRACE_PERIOD`].
+pub fn grace_period_from_env() -> Duration {
+    std::env::var("TRIAGEBOT_SHUTDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_GRACE_PERIOD)
+}
+
+/// Starts a shutdown, see [`channel`].
+pub struct ShutdownTrigger {
+    tx: watch::Sender<Option<Instant>>,
+}
+
+/// Lets a task find out that a shutdown has started, and when its deadline
+/// is.
+#[derive(Clone)]
+pub struct Shutdown {
+    rx: watch::Receiver<Option<Instant>>,
+}
+
+pub fn channel() -> (ShutdownTrigger, Shutdown) {
+    let (tx, rx) = watch::channel(None);
+    (ShutdownTrigger { tx }, Shutdown { rx })
+}
+
+impl ShutdownTrigger {
+    /// Starts shutting down, giving in-flight work `grace_period` to finish.
+    pub fn trigger(&self, grace_period: Duration) {
+        log::info!("shutting down, waiting up to {grace_period:?} for in-flight work");
+        let _ = self.tx.send(Some(Instant::now() + grace_period));
+    }
+
+    /// Waits for SIGTERM or Ctrl-C, then starts shutting down.
+    pub async fn trigger_on_signal(self, grace_period: Duration) {
+        wait_for_signal().await;
+        self.trigger(grace_period);
+    }
+}
+
+impl Shutdown {
+    /// Returns a `Shutdown` that never triggers, for tests and tools.
+    pub fn never() -> Shutdown {
+        let (_, shutdown) = channel();
+        shutdown
+    }
+
+    /// Whether a shutdown has started.
+    pub fn is_requested(&self) -> bool {
+        self.rx.borrow().is_some()
+    }
+
+    /// Completes once a shutdown has started, returning its deadline.
+    pub async fn requested(&mut self) -> Instant {
+        loop {
+            if let Some(deadline) = *self.rx.borrow_and_update() {
+                return deadline;
+            }
+            if self.rx.changed().await.is_err() {
+                // The trigger was dropped without being used, so no shutdown
+                // will ever start.
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Completes once the deadline of a started shutdown has passed.
+    pub async fn deadline(&mut self) {
+        let deadline = self.requested().await;
+        tokio::time::sleep_until(deadline).await;
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+    tokio::select! {
+        _ = sigterm.recv() => log::info!("received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => log::info!("received Ctrl-C"),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+    log::info!("received Ctrl-C");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requested_and_deadline() {
+        let (trigger, mut shutdown) = channel();
+        assert!(!shutdown.is_requested());
+        let other = shutdown.clone();
+
+        trigger.trigger(Duration::from_millis(10));
+        assert!(other.is_requested());
+        let deadline = shutdown.requested().await;
+        shutdown.deadline().await;
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[tokio::test]
+    async fn never_triggers() {
+        let mut shutdown = Shutdown::never();
+        let res = tokio::time::timeout(Duration::from_millis(10), shutdown.requested()).await;
+        assert!(res.is_err());
+        assert!(!shutdown.is_requested());
+    }
+}
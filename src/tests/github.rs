@@ -51,7 +51,9 @@ pub fn issue(
         merged: false,
         draft: false,
         comments: None,
-        comments_url: "".to_string(),
+        comments_url: format!(
+            "https://api.github.com/repos/rust-lang/rust/issues/{number}/comments"
+        ),
         repository: Default::default(),
         base: None,
         head: None,
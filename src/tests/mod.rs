@@ -70,6 +70,7 @@ impl TestContext {
             username: "triagebot-test".to_string(),
             octocrab,
             workqueue: Arc::new(RwLock::new(Default::default())),
+            github_app: None,
         };
 
         Self {
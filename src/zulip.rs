@@ -164,6 +164,8 @@ fn handle_command<'a>(
                 .map_err(|e| format_err!("Failed to parse movement, expected `move <from> <to>`: {e:?}.")),
             Some("meta") => add_meta_notification(&ctx, gh_id, words).await
                 .map_err(|e| format_err!("Failed to parse `meta` command. Synopsis: meta <num> <text>: Add <text> to your notification identified by <num> (>0)\n\nError: {e:?}")),
+            Some("refresh-config") => refresh_config(ctx, words).await
+                .map_err(|e| format_err!("Failed to parse `refresh-config` command. Synopsis: refresh-config <owner/repo>: reloads the triagebot.toml of a repository\n\nError: {e:?}")),
             Some("work") => query_pr_assignments(ctx, gh_id, words).await
                                                                     .map_err(|e| format_err!("Failed to parse `work` command. Synopsis: work <show>: shows your current PRs assignment\n\nError: {e:?}")),
             _ => {
@@ -242,6 +244,20 @@ fn handle_command<'a>(
     })
 }
 
+async fn refresh_config(
+    ctx: &Context,
+    mut words: impl Iterator<Item = &str>,
+) -> anyhow::Result<Option<String>> {
+    let repo = match words.next() {
+        Some(repo) => repo,
+        None => anyhow::bail!("no repository provided"),
+    };
+    if words.next().is_some() {
+        anyhow::bail!("too many words");
+    }
+    Ok(Some(crate::config::refresh(&ctx.github, repo).await?))
+}
+
 async fn query_pr_assignments(
     ctx: &Context,
     gh_id: u64,
@@ -316,21 +332,6 @@ async fn execute_for_other_user(
         assert_eq!(command.pop(), Some(' ')); // pop trailing space
         command
     };
-    let bot_api_token = env::var("ZULIP_API_TOKEN").expect("ZULIP_API_TOKEN");
-
-    let members = ctx
-        .github
-        .raw()
-        .get(format!("{}/api/v1/users", *ZULIP_URL))
-        .basic_auth(&*ZULIP_BOT_EMAIL, Some(&bot_api_token))
-        .send()
-        .await
-        .map_err(|e| format_err!("Failed to get list of zulip users: {e:?}."))?;
-    let members = members
-        .json::<MembersApiResponse>()
-        .await
-        .map_err(|e| format_err!("Failed to get list of zulip users: {e:?}."))?;
-
     // Map GitHub `user_id` to `zulip_user_id`.
     let zulip_user_id = match to_zulip_id(&ctx.github, user_id).await {
         Ok(Some(id)) => id as u64,
@@ -338,11 +339,7 @@ async fn execute_for_other_user(
         Err(e) => anyhow::bail!("Could not find Zulip ID for GitHub id {user_id}: {e:?}"),
     };
 
-    let user = members
-        .members
-        .iter()
-        .find(|m| m.user_id == zulip_user_id)
-        .ok_or_else(|| format_err!("Could not find Zulip user email."))?;
+    let user = get_member(ctx.github.raw(), zulip_user_id).await?;
 
     let output = handle_command(ctx, Ok(user_id), &command, message_data)
         .await?
@@ -379,6 +376,29 @@ async fn execute_for_other_user(
     Ok(Some(output))
 }
 
+/// Fetches the Zulip user with the given ID, which is needed to address
+/// direct messages to them.
+pub async fn get_member(client: &reqwest::Client, zulip_id: u64) -> anyhow::Result<Member> {
+    let bot_api_token = env::var("ZULIP_API_TOKEN").expect("ZULIP_API_TOKEN");
+
+    let members = client
+        .get(format!("{}/api/v1/users", *ZULIP_URL))
+        .basic_auth(&*ZULIP_BOT_EMAIL, Some(&bot_api_token))
+        .send()
+        .await
+        .map_err(|e| format_err!("Failed to get list of zulip users: {e:?}."))?;
+    let members = members
+        .json::<MembersApiResponse>()
+        .await
+        .map_err(|e| format_err!("Failed to get list of zulip users: {e:?}."))?;
+
+    members
+        .members
+        .into_iter()
+        .find(|m| m.user_id == zulip_id)
+        .ok_or_else(|| format_err!("Could not find Zulip user email."))
+}
+
 #[derive(serde::Deserialize)]
 pub struct MembersApiResponse {
     pub members: Vec<Member>,